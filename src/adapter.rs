@@ -0,0 +1,2813 @@
+use std::{
+    cell::Cell,
+    cmp::Ordering,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    hash::{Hash, Hasher},
+    io::{BufRead, BufReader, Read},
+    ops::RangeInclusive,
+    str::FromStr
+};
+use serde::{
+    de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer
+};
+use thiserror::Error;
+
+use crate::{AvailableTime, MessageStatus};
+
+#[derive(Debug, Error)]
+pub enum SharedAdapterError {
+    #[error("Invalid phone number: {0}")]
+    InvalidPhoneNumber(String),
+    #[error("Suspicious phone number: {0}")]
+    SuspiciousPhoneNumber(String),
+    #[error("Blocked phone number: {0}")]
+    BlockedPhoneNumber(String),
+    /// `available` lists the column ids the item did have, when the field was looked for in its columns
+    #[error("Data field not found: {field}{}", available_columns(.available))]
+    DataFieldNotFound {
+        field: String,
+        available: Vec<String>
+    },
+    #[error("Too many items: page exceeds the limit of {0}")]
+    TooManyItems(usize),
+    #[error("Invalid JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    #[error("Invalid date: {0}")]
+    InvalidDate(String),
+    #[error("Invalid encoding: {0}")]
+    InvalidEncoding(String),
+}
+
+fn available_columns(available: &[String]) -> String {
+    if available.is_empty() {
+        String::new()
+    } else {
+        format!(" (available: {})", available.join(", "))
+    }
+}
+
+/// Renders per-status counts as a JSON object keyed by wire name, with every status present
+pub fn status_counts_to_json(counts: &HashMap<MessageStatus, usize>) -> serde_json::Value {
+    let counts = MessageStatus::all()
+        .iter()
+        .map(|status| {
+            let count = counts.get(status).copied().unwrap_or_default();
+            (status.wire_name().to_string(), serde_json::Value::from(count))
+        })
+        .collect();
+
+    serde_json::Value::Object(counts)
+}
+
+/// The provider statuses that `mapping` has no entry for, in the order given, so a
+/// migration can be checked for gaps before it runs. Matching is exact.
+pub fn validate_status_mapping(mapping: &HashMap<String, MessageStatus>, provider_statuses: &[&str]) -> Vec<String> {
+    provider_statuses
+        .iter()
+        .filter(|status| !mapping.contains_key(**status))
+        .map(|status| status.to_string())
+        .collect()
+}
+
+/// Represents a status update regarding a WhatsApp message
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatusUpdate {
+    pub recipient_id: String,
+    pub status: MessageStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<StatusError>,
+    /// WhatsApp's message id (`wamid`), used to recognize redelivered events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+}
+
+/// The reason WhatsApp gives for a `failed` message
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct StatusError {
+    pub code: i64,
+    pub title: String,
+}
+
+impl StatusUpdate {
+    /// The recipient in the same E.164 form as `LeadDetails::dedup_key`. WhatsApp sends
+    /// bare numbers that already include the country code, such as `15551234567`.
+    pub fn normalized_recipient(&self) -> Result<String, SharedAdapterError> {
+        let phone_number = normalize_phone(format!("+{}", self.recipient_id))?;
+        Ok(format!("+{}", phone_number))
+    }
+
+    /// Whether `recipient_id` looks like a WhatsApp ID: 7 to 15 digits without a `+`, the
+    /// same length `PhoneNumber::from_e164` accepts, so valid WAIDs always convert
+    pub fn is_valid_waid(&self) -> bool {
+        E164_DIGITS.contains(&self.recipient_id.len()) && self.recipient_id.chars().all(|c| c.is_ascii_digit())
+    }
+
+    /// The phone number behind the WhatsApp ID. WAIDs already include the country code,
+    /// so this usually just adds the `+`.
+    pub fn to_phone_number(&self) -> Result<PhoneNumber, SharedAdapterError> {
+        if !self.is_valid_waid() {
+            return Err(SharedAdapterError::InvalidPhoneNumber(self.recipient_id.clone()));
+        }
+
+        PhoneNumber::from_e164(&format!("+{}", self.recipient_id))
+    }
+
+    /// Compact encoding for internal queues: the status index as one byte, then the
+    /// recipient id as UTF-8 prefixed with its length as a big-endian `u32`.
+    /// The `error` details are not encoded.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let recipient_id = self.recipient_id.as_bytes();
+
+        let mut bytes = Vec::with_capacity(5 + recipient_id.len());
+        bytes.push(self.status.to_index());
+        bytes.extend_from_slice(&(recipient_id.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(recipient_id);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<StatusUpdate, SharedAdapterError> {
+        let (&index, rest) = bytes
+            .split_first()
+            .ok_or(SharedAdapterError::InvalidEncoding("empty buffer".to_string()))?;
+        let status = MessageStatus::from_index(index)
+            .ok_or_else(|| SharedAdapterError::InvalidEncoding(format!("unknown status index {}", index)))?;
+
+        let (length, rest) = rest
+            .split_first_chunk::<4>()
+            .ok_or(SharedAdapterError::InvalidEncoding("truncated length".to_string()))?;
+        let length = u32::from_be_bytes(*length) as usize;
+
+        if rest.len() != length {
+            return Err(SharedAdapterError::InvalidEncoding(format!("expected {} recipient bytes, got {}", length, rest.len())));
+        }
+
+        let recipient_id = String::from_utf8(rest.to_vec())
+            .map_err(|_| SharedAdapterError::InvalidEncoding("recipient id is not UTF-8".to_string()))?;
+
+        Ok(StatusUpdate {
+            recipient_id,
+            status,
+            error: None,
+            message_id: None
+        })
+    }
+
+    /// Pulls every status out of a WhatsApp Cloud API webhook payload
+    /// (`entry[].changes[].value.statuses[]`), skipping entries without a recipient or status
+    pub fn from_whatsapp_webhook(payload: &serde_json::Value) -> Vec<StatusUpdate> {
+        let statuses = payload["entry"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|entry| entry["changes"].as_array().into_iter().flatten())
+            .flat_map(|change| change["value"]["statuses"].as_array().into_iter().flatten());
+
+        statuses
+            .filter_map(|status| {
+                let recipient_id = status["recipient_id"].as_str()?.to_string();
+                let message_id = status["id"].as_str().map(|id| id.to_string());
+                let status_value = MessageStatus::from_string(status["status"].as_str()?);
+
+                let error = if status_value == MessageStatus::Failed {
+                    status["errors"].get(0).and_then(|error| {
+                        Some(StatusError {
+                            code: error["code"].as_i64()?,
+                            title: error["title"].as_str().unwrap_or_default().to_string()
+                        })
+                    })
+                } else {
+                    None
+                };
+
+                Some(StatusUpdate {
+                    recipient_id,
+                    status: status_value,
+                    error,
+                    message_id
+                })
+            })
+            .collect()
+    }
+}
+
+/// Folds a stream of status updates into the latest known status per recipient.
+/// Updates that arrive out of order are ignored if they would move a recipient backwards.
+#[derive(Debug, Default)]
+pub struct StatusReconciler {
+    statuses: HashMap<String, MessageStatus>,
+}
+
+impl StatusReconciler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, update: StatusUpdate) {
+        match self.statuses.get_mut(&update.recipient_id) {
+            Some(current) => {
+                if Self::accepts(current, &update) {
+                    *current = update.status;
+                }
+            }
+            None => {
+                self.statuses.insert(update.recipient_id, update.status);
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> &HashMap<String, MessageStatus> {
+        &self.statuses
+    }
+
+    // Kept separate from `record` so a timestamp-aware rule can replace it later
+    fn accepts(current: &MessageStatus, update: &StatusUpdate) -> bool {
+        current.can_transition_to(&update.status)
+    }
+}
+
+/// Tracks the highest status per recipient by `MessageStatus`'s ordering and keeps a log of
+/// every update that would have moved a recipient backwards, for debugging lost progress.
+/// Unlike `StatusReconciler` it does not apply `can_transition_to`, so a terminal status can
+/// still be replaced by a higher one.
+#[derive(Debug, Default)]
+pub struct StatusTracker {
+    statuses: HashMap<String, MessageStatus>,
+    downgrades: Vec<Downgrade>,
+}
+
+/// An update that arrived with a lower status than the one already tracked
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Downgrade {
+    pub recipient: String,
+    pub from: MessageStatus,
+    pub to: MessageStatus,
+}
+
+impl StatusTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the recipient to `status` unless it is lower than the current one, in which
+    /// case the attempt is logged and the current status kept
+    pub fn update(&mut self, recipient: &str, status: MessageStatus) {
+        match self.statuses.get_mut(recipient) {
+            Some(current) if status < *current => self.downgrades.push(Downgrade {
+                recipient: recipient.to_string(),
+                from: current.clone(),
+                to: status
+            }),
+            Some(current) => *current = status,
+            None => {
+                self.statuses.insert(recipient.to_string(), status);
+            }
+        }
+    }
+
+    pub fn status(&self, recipient: &str) -> Option<&MessageStatus> {
+        self.statuses.get(recipient)
+    }
+
+    /// Every attempted downgrade, in the order they happened
+    pub fn downgrades(&self) -> &[Downgrade] {
+        &self.downgrades
+    }
+}
+
+/// A `recipient_id,status` CSV of the latest status per recipient, folded with the same
+/// rules as `StatusReconciler`. Rows follow the order recipients first appear in.
+pub fn status_updates_to_csv(updates: &[StatusUpdate]) -> String {
+    let mut reconciler = StatusReconciler::new();
+    let mut recipients: Vec<&str> = Vec::new();
+    for update in updates {
+        if !reconciler.snapshot().contains_key(&update.recipient_id) {
+            recipients.push(&update.recipient_id);
+        }
+        reconciler.record(update.clone());
+    }
+
+    let mut csv = String::from("recipient_id,status\n");
+    for recipient in recipients {
+        let status = &reconciler.snapshot()[recipient];
+        csv.push_str(&format!("{},{}\n", csv_field(recipient), csv_field(status.wire_name())));
+    }
+    csv
+}
+
+/// Quotes a CSV field when it contains a delimiter, quote or line break, doubling any quotes
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Recipients whose message took longer than `sla_seconds` to be delivered after being
+/// sent, or that were sent but never delivered. Events are `(recipient, status, timestamp)`;
+/// reaching `Read` or `Responded` counts as delivered. Recipients are returned in the order
+/// they were first sent to.
+pub fn sla_breaches(events: &[(String, MessageStatus, i64)], sla_seconds: i64) -> Vec<String> {
+    let mut sent_at: Vec<(&str, i64)> = Vec::new();
+    let mut delivered_at: HashMap<&str, i64> = HashMap::new();
+
+    for (recipient, status, timestamp) in events {
+        if *status == MessageStatus::Sent {
+            match sent_at.iter_mut().find(|(sent, _)| *sent == recipient) {
+                Some((_, first_sent)) => *first_sent = (*first_sent).min(*timestamp),
+                None => sent_at.push((recipient, *timestamp))
+            }
+        } else if status.is_success() {
+            delivered_at
+                .entry(recipient)
+                .and_modify(|first_delivered| *first_delivered = (*first_delivered).min(*timestamp))
+                .or_insert(*timestamp);
+        }
+    }
+
+    sent_at
+        .into_iter()
+        .filter(|(recipient, sent)| match delivered_at.get(recipient) {
+            Some(delivered) => delivered - sent > sla_seconds,
+            None => true
+        })
+        .map(|(recipient, _)| recipient.to_string())
+        .collect()
+}
+
+/// A recipient's current status and the unix timestamp it changed to it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct StatusAge {
+    pub status: MessageStatus,
+    pub changed_at: i64,
+}
+
+/// Recipients that have been in `status` for more than `max_age_secs` as of `now`, in the
+/// order given, e.g. messages stuck in `Sent`
+pub fn stale_in_status(ages: &[(String, StatusAge)], status: MessageStatus, max_age_secs: i64, now: i64) -> Vec<String> {
+    ages
+        .iter()
+        .filter(|(_, age)| age.status == status && now - age.changed_at > max_age_secs)
+        .map(|(recipient, _)| recipient.clone())
+        .collect()
+}
+
+/// A text message to send to a recipient
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OutboundMessage {
+    pub to: String,
+    pub body: String,
+}
+
+/// A messaging backend: how outbound messages are encoded and how its status callbacks are read
+pub trait MessageProvider {
+    fn format_outbound(&self, msg: &OutboundMessage) -> serde_json::Value;
+    fn parse_status(&self, json: &serde_json::Value) -> Vec<StatusUpdate>;
+}
+
+/// The WhatsApp Cloud API
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WhatsappProvider;
+
+impl MessageProvider for WhatsappProvider {
+    fn format_outbound(&self, msg: &OutboundMessage) -> serde_json::Value {
+        serde_json::json!({
+            "messaging_product": "whatsapp",
+            "recipient_type": "individual",
+            "to": msg.to,
+            "type": "text",
+            "text": { "body": msg.body }
+        })
+    }
+
+    fn parse_status(&self, json: &serde_json::Value) -> Vec<StatusUpdate> {
+        StatusUpdate::from_whatsapp_webhook(json)
+    }
+}
+
+/// Keywords that, sent on their own, mean the user wants no more messages
+const OPT_OUT_KEYWORDS: &[&str] = &["stop", "unsubscribe", "cancel", "end", "quit"];
+
+/// Whether an inbound message is an opt-out request. The whole message has to be one of
+/// the keywords, ignoring case and surrounding whitespace.
+pub fn detect_opt_out(message_body: &str) -> bool {
+    let message_body = message_body.trim().to_lowercase();
+    OPT_OUT_KEYWORDS.contains(&message_body.as_str())
+}
+
+/// An `Unsubscribed` update for the sender when their message is an opt-out request
+pub fn opt_out_update(sender: &str, message_body: &str) -> Option<StatusUpdate> {
+    detect_opt_out(message_body).then(|| StatusUpdate {
+        recipient_id: sender.to_string(),
+        status: MessageStatus::Unsubscribed,
+        error: None,
+        message_id: None
+    })
+}
+
+/// Remembers the most recently seen `(message_id, status)` pairs so redelivered webhook
+/// events can be skipped. Holds at most `capacity` pairs, evicting the least recently seen.
+#[derive(Debug)]
+pub struct StatusDeduper {
+    capacity: usize,
+    /// Every remembered pair, with the generation it was last seen in
+    seen: HashMap<(String, MessageStatus), u64>,
+    /// Sightings oldest first. A pair seen again leaves its older sightings behind, which
+    /// are skipped when evicting and dropped once they outnumber the live ones.
+    order: VecDeque<(u64, (String, MessageStatus))>,
+    generation: u64,
+}
+
+impl StatusDeduper {
+    pub fn new(capacity: usize) -> Self {
+        StatusDeduper {
+            capacity,
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            generation: 0
+        }
+    }
+
+    /// Whether this update hasn't been seen before, remembering it either way.
+    /// Updates without a message id can't be deduplicated and always count as new.
+    pub fn is_new(&mut self, update: &StatusUpdate) -> bool {
+        let Some(message_id) = &update.message_id else {
+            return true;
+        };
+        if self.capacity == 0 {
+            return true;
+        }
+        let key = (message_id.clone(), update.status.clone());
+        self.generation += 1;
+
+        let is_new = self.seen.insert(key.clone(), self.generation).is_none();
+        if is_new && self.seen.len() > self.capacity {
+            self.evict_oldest();
+        }
+        self.order.push_back((self.generation, key));
+
+        if self.order.len() > 2 * self.capacity {
+            let seen = &self.seen;
+            self.order.retain(|(generation, key)| seen.get(key) == Some(generation));
+        }
+
+        is_new
+    }
+
+    fn evict_oldest(&mut self) {
+        while let Some((generation, key)) = self.order.pop_front() {
+            if self.seen.get(&key) == Some(&generation) {
+                self.seen.remove(&key);
+                return;
+            }
+        }
+    }
+}
+
+/// Represents a completed appointment request.
+/// Only `phone_number` and `requested_date` are required when deserializing.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AppointmentRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub phone_number: String,
+    #[serde(default, deserialize_with = "deserialize_availabilities")]
+    pub availabilities: Vec<AvailableTime>,
+    #[serde(default)]
+    pub additional_information: String,
+    pub requested_date: String
+}
+
+/// Accepts `"morning"` as well as `["morning"]`, since the bot sends either
+fn deserialize_availabilities<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<AvailableTime>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(AvailableTime),
+        Many(Vec<AvailableTime>)
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(time) => vec![time],
+        OneOrMany::Many(times) => times
+    })
+}
+
+impl AppointmentRequest {
+    /// Compares two requests for dedup purposes, treating availabilities as a set
+    /// so their order and any repeats don't matter
+    pub fn semantically_eq(&self, other: &AppointmentRequest) -> bool {
+        self.name == other.name
+            && self.phone_number == other.phone_number
+            && self.requested_date == other.requested_date
+            && self.additional_information == other.additional_information
+            && self.availabilities.iter().collect::<HashSet<_>>() == other.availabilities.iter().collect::<HashSet<_>>()
+    }
+
+    /// The notes with control characters (other than newlines) removed, cut to at most
+    /// `max_len` characters. Truncated notes end in `…`, which counts towards the limit.
+    pub fn sanitized_notes(&self, max_len: usize) -> String {
+        let notes: Vec<char> = self.additional_information
+            .chars()
+            .filter(|c| *c == '\n' || !c.is_control())
+            .collect();
+
+        if notes.len() <= max_len {
+            return notes.into_iter().collect();
+        }
+
+        let mut truncated: String = notes[..max_len.saturating_sub(1)].iter().collect();
+        if max_len > 0 {
+            truncated.push('…');
+        }
+        truncated
+    }
+
+    /// The availabilities that name an actual slot, i.e. without `Unknown`
+    pub fn concrete_availabilities(&self) -> impl Iterator<Item = AvailableTime> + '_ {
+        self.availabilities
+            .iter()
+            .filter(|time| **time != AvailableTime::Unknown)
+            .cloned()
+    }
+
+    /// The earliest slot in the day that both the lead and the advisor are available for.
+    /// `Unknown` never matches, since it doesn't say when anyone is free.
+    pub fn preferred_slot(&self, advisor: &[AvailableTime]) -> Option<AvailableTime> {
+        [AvailableTime::Morning, AvailableTime::Afternoon, AvailableTime::Evening]
+            .into_iter()
+            .find(|slot| self.availabilities.contains(slot) && advisor.contains(slot))
+    }
+
+    /// How much usable information the request carries, from 0 to 100, for triage.
+    ///
+    /// A phone that normalizes and a real `YYYY-MM-DD` date are worth 30 each, since
+    /// neither can be booked without. At least one known availability adds 20, and a
+    /// non-blank name and non-blank notes add 10 each.
+    pub fn completeness(&self) -> u8 {
+        let mut score = 0;
+
+        if normalize_phone(self.phone_number.clone()).is_ok() {
+            score += 30;
+        }
+        if is_valid_date(&self.requested_date) {
+            score += 30;
+        }
+        if self.concrete_availabilities().next().is_some() {
+            score += 20;
+        }
+        if self.name.as_deref().is_some_and(|name| !name.trim().is_empty()) {
+            score += 10;
+        }
+        if !self.additional_information.trim().is_empty() {
+            score += 10;
+        }
+
+        score
+    }
+
+    /// Reads an appointment request back out of a Monday item. The phone and date columns
+    /// are required; availability defaults to none and notes to empty.
+    pub fn from_item(item: &Item, map: &AppointmentColumnMap) -> Result<AppointmentRequest, SharedAdapterError> {
+        let phone_number = item.column_text(&map.phone).ok_or(SharedAdapterError::DataFieldNotFound { field: map.phone.clone(), available: Vec::new() })?;
+        let requested_date = item.column_text(&map.date).ok_or(SharedAdapterError::DataFieldNotFound { field: map.date.clone(), available: Vec::new() })?;
+
+        let availabilities = item.column_text(&map.availability)
+            .map(|availability| availability.split(',').map(AvailableTime::from_free_text).collect())
+            .unwrap_or_default();
+
+        Ok(AppointmentRequest {
+            name: item.name.clone(),
+            phone_number: phone_number.to_string(),
+            availabilities,
+            additional_information: item.column_text(&map.notes).unwrap_or_default().to_string(),
+            requested_date: requested_date.to_string()
+        })
+    }
+
+    /// Reads every appointment request in a page, returning the items that couldn't be read
+    /// as errors alongside the ones that could
+    pub fn all_from_page(page: &ItemsPage, map: &AppointmentColumnMap) -> (Vec<AppointmentRequest>, Vec<SharedAdapterError>) {
+        let mut requests = Vec::new();
+        let mut errors = Vec::new();
+
+        for item in &page.items {
+            match AppointmentRequest::from_item(item, map) {
+                Ok(request) => requests.push(request),
+                Err(error) => errors.push(error)
+            }
+        }
+
+        (requests, errors)
+    }
+
+    /// Builds the item name and `column_values` payload for creating this request as a Monday item.
+    /// The item is named after the lead, falling back to the phone number when there's no name.
+    /// The phone is normalized first, so a number that doesn't normalize is an error.
+    pub fn to_monday_item(&self, map: &AppointmentColumnMap) -> Result<(String, serde_json::Value), SharedAdapterError> {
+        let phone_number = PhoneNumber::parse(&self.phone_number)?;
+        let item_name = self.name.clone().unwrap_or_else(|| self.phone_number.clone());
+
+        let availability = self.availabilities
+            .iter()
+            .cloned()
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // Monday's date column only takes the date part
+        let date = self.requested_date.split('T').next().unwrap_or_default();
+
+        let mut column_values = serde_json::Map::new();
+        column_values.insert(map.phone.clone(), ColumnValue::phone_change(&phone_number));
+        column_values.insert(map.availability.clone(), ColumnValue::text_change(&availability));
+        column_values.insert(map.notes.clone(), ColumnValue::text_change(&self.additional_information));
+        column_values.insert(map.date.clone(), serde_json::json!({ "date": date }));
+
+        Ok((item_name, serde_json::Value::Object(column_values)))
+    }
+}
+
+/// Parses the compact `name|phone|morning,evening|notes|YYYY-MM-DD` form used for quick
+/// testing. The name and notes may be empty; the phone and date are validated.
+impl FromStr for AppointmentRequest {
+    type Err = SharedAdapterError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = line.split('|').map(|field| field.trim()).collect();
+        let [name, phone_number, availabilities, notes, requested_date] = fields[..] else {
+            return Err(SharedAdapterError::InvalidEncoding(format!("expected 5 fields, got {}", fields.len())));
+        };
+
+        if !is_valid_date(requested_date) {
+            return Err(SharedAdapterError::InvalidDate(requested_date.to_string()));
+        }
+
+        Ok(AppointmentRequest {
+            name: Some(name.to_string()).filter(|name| !name.is_empty()),
+            phone_number: normalize_phone(phone_number.to_string())?,
+            availabilities: availabilities
+                .split(',')
+                .map(|time| time.trim())
+                .filter(|time| !time.is_empty())
+                .map(|time| AvailableTime::from(time.to_string()))
+                .collect(),
+            additional_information: notes.to_string(),
+            requested_date: requested_date.to_string()
+        })
+    }
+}
+
+/// Whether a string is a real calendar date in `YYYY-MM-DD` form
+fn is_valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return false;
+    };
+
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+
+    let (Ok(year), Ok(month), Ok(day)) = (year.parse::<u32>(), month.parse::<u32>(), day.parse::<u32>()) else {
+        return false;
+    };
+
+    let is_leap_year = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year => 29,
+        2 => 28,
+        _ => return false
+    };
+
+    (1..=days_in_month).contains(&day)
+}
+
+/// Monday column ids that appointment request fields are stored in
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AppointmentColumnMap {
+    pub phone: String,
+    pub availability: String,
+    pub notes: String,
+    pub date: String,
+}
+
+/// Width, in characters, of the longest bar drawn by `availability_histogram`
+const HISTOGRAM_WIDTH: usize = 40;
+
+/// A plain-text bar chart of how many requests ask for each slot, one line per slot
+/// like `morning   ██████ 6`. Bars are scaled so the most requested slot is
+/// `HISTOGRAM_WIDTH` wide. `Unknown` slots are left out.
+pub fn availability_histogram(reqs: &[AppointmentRequest]) -> String {
+    let slots = [AvailableTime::Morning, AvailableTime::Afternoon, AvailableTime::Evening];
+
+    let counts = slots.clone().map(|slot| {
+        reqs.iter()
+            .filter(|request| request.availabilities.contains(&slot))
+            .count()
+    });
+    let max_count = counts.iter().copied().max().unwrap_or_default().max(1);
+
+    slots
+        .into_iter()
+        .zip(counts)
+        .map(|(slot, count)| {
+            let bar = "█".repeat(count * HISTOGRAM_WIDTH / max_count);
+            format!("{:<10}{} {}\n", String::from(slot), bar, count)
+        })
+        .collect()
+}
+
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Item {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    /// Missing when the query selected no columns; every accessor treats that as no columns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    column_values: Option<Vec<HashMap<String, serde_json::Value>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    group: Option<GroupRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    subitems: Option<Vec<Item>>,
+}
+
+/// Monday's `group { id }` selection on an item
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct GroupRef {
+    id: String,
+}
+
+impl Item {
+    /// Id of the board group the item belongs to, when the query selected `group { id }`
+    pub fn group_id(&self) -> Option<&str> {
+        self.group.as_ref().map(|group| group.id.as_str())
+    }
+
+    /// The item's subitems, empty when it has none or the query didn't select them
+    pub fn subitems(&self) -> &[Item] {
+        self.subitems.as_deref().unwrap_or_default()
+    }
+
+    /// The `text` of the column with the given id, if the item has it
+    pub fn column_text(&self, column_id: &str) -> Option<&str> {
+        self.column(column_id).and_then(column_text)
+    }
+
+    /// Ids of the people and teams assigned in a `people` column, empty when unassigned
+    pub fn assignee_ids(&self, people_col: &str) -> Vec<u64> {
+        let Some(value) = self.column(people_col).and_then(column_json_value) else {
+            return Vec::new();
+        };
+
+        value["personsAndTeams"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|assignee| assignee["id"].as_u64())
+            .collect()
+    }
+
+    /// The item's columns that have something in them, in board order. Columns without an
+    /// id are skipped. These are built from the raw column values, so they are owned.
+    pub fn non_empty_columns(&self) -> Vec<ColumnValue> {
+        self.column_values
+            .iter()
+            .flatten()
+            .filter_map(|column_value| ColumnValue::from_raw(column_value).ok())
+            .filter(|column_value| !column_value.is_empty())
+            .collect()
+    }
+
+    fn column(&self, id: &str) -> Option<&HashMap<String, serde_json::Value>> {
+        self.column_values
+            .iter()
+            .flatten()
+            .find(|column_value| column_id(column_value) == Some(id))
+    }
+}
+
+/// Builds an `Item` without hand-writing Monday's column JSON, e.g. for tests.
+///
+/// ```
+/// use shared_monday::ItemBuilder;
+///
+/// let item = ItemBuilder::new()
+///     .name("Jane")
+///     .id("1234")
+///     .column("phone", "15551234567")
+///     .build();
+///
+/// assert_eq!(item.column_text("phone"), Some("15551234567"));
+/// ```
+#[derive(Debug, Default)]
+pub struct ItemBuilder {
+    name: Option<String>,
+    id: Option<String>,
+    column_values: Vec<HashMap<String, serde_json::Value>>,
+}
+
+impl ItemBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    pub fn column(mut self, id: &str, text: &str) -> Self {
+        let mut column_value = HashMap::new();
+        column_value.insert("id".to_string(), serde_json::Value::from(id));
+        column_value.insert("text".to_string(), serde_json::Value::from(text));
+        self.column_values.push(column_value);
+        self
+    }
+
+    pub fn build(self) -> Item {
+        Item {
+            name: self.name,
+            id: self.id,
+            column_values: Some(self.column_values),
+            group: None,
+            subitems: None
+        }
+    }
+}
+
+
+/// A single typed entry of an item's `column_values`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ColumnValue {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+}
+
+impl ColumnValue {
+    pub fn from_raw(map: &HashMap<String, serde_json::Value>) -> Result<ColumnValue, SharedAdapterError> {
+        let id = map
+            .get("id")
+            .and_then(|id| id.as_str())
+            .ok_or(SharedAdapterError::DataFieldNotFound { field: "id".to_string(), available: Vec::new() })?
+            .to_string();
+
+        let text = column_text(map).map(|text| text.to_string());
+        let value = map.get("value").filter(|value| !value.is_null()).cloned();
+
+        Ok(ColumnValue {
+            id,
+            text,
+            value
+        })
+    }
+
+    /// Whether Monday considers the column empty: no text, and a value that is missing,
+    /// `null` or an empty object (which Monday sends as the string `"{}"`)
+    pub fn is_empty(&self) -> bool {
+        let empty_text = self.text.as_deref().is_none_or(str::is_empty);
+        let empty_value = match &self.value {
+            None | Some(serde_json::Value::Null) => true,
+            Some(serde_json::Value::String(value)) => value.is_empty() || value == "{}",
+            Some(serde_json::Value::Object(value)) => value.is_empty(),
+            Some(_) => false
+        };
+
+        empty_text && empty_value
+    }
+
+    /// The value a status column is set to for a message status, by label
+    pub fn status_change(status: &MessageStatus) -> serde_json::Value {
+        serde_json::json!({ "label": status.wire_name() })
+    }
+
+    /// The value a phone column is set to. The country is taken from the number's calling
+    /// code and left out when the code isn't known.
+    pub fn phone_change(phone_number: &PhoneNumber) -> serde_json::Value {
+        match country_short_name(phone_number.as_str().trim_start_matches('+')) {
+            Some(country) => serde_json::json!({ "phone": phone_number.as_str(), "countryShortName": country }),
+            None => serde_json::json!({ "phone": phone_number.as_str() })
+        }
+    }
+
+    /// The value a text column is set to
+    pub fn text_change(text: &str) -> serde_json::Value {
+        serde_json::Value::from(text)
+    }
+}
+
+/// The id of a column on a Monday board
+pub type ColumnId = String;
+
+/// The `column_values` object for a `change_multiple_column_values` mutation,
+/// touching only the given columns
+pub fn column_change_set(changes: &[(ColumnId, serde_json::Value)]) -> serde_json::Value {
+    serde_json::Value::Object(changes.iter().cloned().collect())
+}
+
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ItemsPage {
+    items: Vec<Item>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cursor: Option<String>,
+}
+
+impl ItemsPage {
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Cursor for fetching the next page, `None` on the last page
+    pub fn cursor(&self) -> Option<&str> {
+        self.cursor.as_deref()
+    }
+
+    /// Parses a page, failing with `TooManyItems` as soon as the `items` array grows past
+    /// `max_items` rather than after the whole array has been built
+    pub fn from_json_limited(json: &str, max_items: usize) -> Result<ItemsPage, SharedAdapterError> {
+        let exceeded = Cell::new(false);
+        let seed = LimitedItemsPage {
+            max_items,
+            exceeded: &exceeded
+        };
+
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let page = seed
+            .deserialize(&mut deserializer)
+            .and_then(|page| deserializer.end().map(|_| page));
+
+        page.map_err(|error| {
+            if exceeded.get() {
+                SharedAdapterError::TooManyItems(max_items)
+            } else {
+                SharedAdapterError::InvalidJson(error)
+            }
+        })
+    }
+
+    /// A deterministic JSON form for snapshot tests: each item's columns are sorted by id
+    /// and null fields are dropped, so Monday's field ordering doesn't matter
+    pub fn to_canonical_json(&self) -> serde_json::Value {
+        let mut page = self.clone();
+        for item in &mut page.items {
+            if let Some(column_values) = &mut item.column_values {
+                column_values.sort_by(|a, b| column_id(a).cmp(&column_id(b)));
+            }
+        }
+
+        let mut json = serde_json::to_value(page).unwrap_or_default();
+        strip_nulls(&mut json);
+        json
+    }
+
+    /// Appends `other`'s items, taking its cursor since it is the later page
+    pub fn merge(&mut self, other: ItemsPage) {
+        self.items.extend(other.items);
+        self.cursor = other.cursor;
+    }
+
+    /// Converts every item into a lead, skipping the ones that fail to convert
+    pub fn into_leads(self) -> LeadIter {
+        LeadIter {
+            items: self.items.into_iter(),
+            skipped: 0
+        }
+    }
+}
+
+struct LimitedItemsPage<'a> {
+    max_items: usize,
+    exceeded: &'a Cell<bool>,
+}
+
+impl<'de> DeserializeSeed<'de> for LimitedItemsPage<'_> {
+    type Value = ItemsPage;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for LimitedItemsPage<'_> {
+    type Value = ItemsPage;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an items page")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut items = None;
+        let mut cursor = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "items" => items = Some(map.next_value_seed(LimitedItems {
+                    max_items: self.max_items,
+                    exceeded: self.exceeded
+                })?),
+                "cursor" => cursor = map.next_value()?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(ItemsPage {
+            items: items.ok_or_else(|| de::Error::missing_field("items"))?,
+            cursor
+        })
+    }
+}
+
+struct LimitedItems<'a> {
+    max_items: usize,
+    exceeded: &'a Cell<bool>,
+}
+
+impl<'de> DeserializeSeed<'de> for LimitedItems<'_> {
+    type Value = Vec<Item>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for LimitedItems<'_> {
+    type Value = Vec<Item>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of items")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut items = Vec::new();
+
+        while let Some(item) = seq.next_element::<Item>()? {
+            if items.len() == self.max_items {
+                self.exceeded.set(true);
+                return Err(de::Error::custom("too many items"));
+            }
+            items.push(item);
+        }
+
+        Ok(items)
+    }
+}
+
+fn strip_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|_, value| !value.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+/// Folds fetched pages, in order, into one logical page
+pub fn merge_pages(pages: Vec<ItemsPage>) -> ItemsPage {
+    pages.into_iter().fold(ItemsPage::default(), |mut merged, page| {
+        merged.merge(page);
+        merged
+    })
+}
+
+/// Parses a JSON array of items one item at a time, so a huge `items` array never has to
+/// be held in memory as a whole. The reader must contain just the array. The iterator
+/// stops after the first error.
+pub fn stream_items<R: Read>(reader: R) -> impl Iterator<Item = Result<Item, SharedAdapterError>> {
+    ItemStream {
+        reader: BufReader::new(reader),
+        started: false,
+        done: false
+    }
+}
+
+struct ItemStream<R> {
+    reader: BufReader<R>,
+    started: bool,
+    done: bool,
+}
+
+impl<R: Read> ItemStream<R> {
+    /// Skips whitespace and consumes the next byte, `None` at end of input
+    fn next_byte(&mut self) -> Result<Option<u8>, serde_json::Error> {
+        loop {
+            let buffer = self.reader.fill_buf().map_err(serde_json::Error::io)?;
+            let Some(&byte) = buffer.first() else {
+                return Ok(None);
+            };
+            self.reader.consume(1);
+
+            if !byte.is_ascii_whitespace() {
+                return Ok(Some(byte));
+            }
+        }
+    }
+
+    fn peek_byte(&mut self) -> Result<Option<u8>, serde_json::Error> {
+        loop {
+            let buffer = self.reader.fill_buf().map_err(serde_json::Error::io)?;
+            match buffer.first() {
+                Some(byte) if byte.is_ascii_whitespace() => self.reader.consume(1),
+                byte => return Ok(byte.copied())
+            }
+        }
+    }
+
+    fn next_item(&mut self) -> Result<Option<Item>, serde_json::Error> {
+        let unexpected = |expected: &str| <serde_json::Error as de::Error>::custom(format!("expected {}", expected));
+
+        if !self.started {
+            self.started = true;
+            if self.next_byte()? != Some(b'[') {
+                return Err(unexpected("`[`"));
+            }
+            if self.peek_byte()? == Some(b']') {
+                self.reader.consume(1);
+                return Ok(None);
+            }
+        } else {
+            match self.next_byte()? {
+                Some(b',') => {}
+                Some(b']') => return Ok(None),
+                _ => return Err(unexpected("`,` or `]`"))
+            }
+        }
+
+        // Objects end at their closing brace, so the deserializer never reads past the item
+        let mut deserializer = serde_json::Deserializer::from_reader(&mut self.reader);
+        Item::deserialize(&mut deserializer).map(Some)
+    }
+}
+
+impl<R: Read> Iterator for ItemStream<R> {
+    type Item = Result<Item, SharedAdapterError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let item = self.next_item().transpose();
+        if !matches!(item, Some(Ok(_))) {
+            self.done = true;
+        }
+        item.map(|item| item.map_err(SharedAdapterError::InvalidJson))
+    }
+}
+
+impl FromIterator<Item> for ItemsPage {
+    fn from_iter<I: IntoIterator<Item = Item>>(iter: I) -> Self {
+        ItemsPage {
+            items: iter.into_iter().collect(),
+            cursor: None
+        }
+    }
+}
+
+impl Extend<Item> for ItemsPage {
+    fn extend<I: IntoIterator<Item = Item>>(&mut self, iter: I) {
+        self.items.extend(iter);
+    }
+}
+
+/// Iterator returned by `ItemsPage::into_leads`
+#[derive(Debug)]
+pub struct LeadIter {
+    items: std::vec::IntoIter<Item>,
+    skipped: usize,
+}
+
+impl LeadIter {
+    /// Number of items skipped so far because they could not be converted
+    pub fn skipped_count(&self) -> usize {
+        self.skipped
+    }
+}
+
+impl Iterator for LeadIter {
+    type Item = LeadDetails;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for item in self.items.by_ref() {
+            match LeadDetails::try_from(&item) {
+                Ok(lead) => return Some(lead),
+                Err(_) => self.skipped += 1
+            }
+        }
+
+        None
+    }
+}
+
+
+/// A validated lead. Serializes as a flat `name`/`phone_number` object, so it can be
+/// embedded in larger payloads with `#[serde(flatten)]`. The phone is written in E.164 form
+/// and deserializing validates it like `LeadDetails::new` does.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[serde(try_from = "RawLeadDetails")]
+pub struct LeadDetails {
+    pub name: String,
+    #[serde(serialize_with = "serialize_e164")]
+    phone_number: String
+}
+
+/// The unvalidated wire form of `LeadDetails`
+#[derive(Deserialize)]
+struct RawLeadDetails {
+    name: String,
+    phone_number: String
+}
+
+impl TryFrom<RawLeadDetails> for LeadDetails {
+    type Error = SharedAdapterError;
+
+    fn try_from(raw: RawLeadDetails) -> Result<Self, Self::Error> {
+        LeadDetails::new(raw.name, raw.phone_number)
+    }
+}
+
+/// Writes a stored phone with its `+`, so it reads back unambiguously
+fn serialize_e164<S: Serializer>(phone_number: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&format_args!("+{}", phone_number))
+}
+
+impl LeadDetails {
+    pub fn new(name: String, phone_number: String) -> Result<LeadDetails, SharedAdapterError> {
+        let phone_number = normalize_phone(phone_number)?;
+
+        Ok(LeadDetails {
+            name,
+            phone_number
+        })
+    }
+
+    /// Like `new`, but also rejects numbers that are obviously made up, such as
+    /// `0000000000` or `1234567890`
+    pub fn new_strict(name: String, phone_number: String) -> Result<LeadDetails, SharedAdapterError> {
+        let lead = LeadDetails::new(name, phone_number)?;
+
+        if is_suspicious_phone(&lead.phone_number) {
+            return Err(SharedAdapterError::SuspiciousPhoneNumber(lead.phone_number));
+        }
+
+        Ok(lead)
+    }
+
+    /// Like `new`, but rejects numbers on the blocklist, however either side was formatted
+    pub fn new_checked(name: String, phone_number: String, blocklist: &Blocklist) -> Result<LeadDetails, SharedAdapterError> {
+        let lead = LeadDetails::new(name, phone_number)?;
+
+        if blocklist.0.contains(&lead.phone_number) {
+            return Err(SharedAdapterError::BlockedPhoneNumber(lead.phone_number));
+        }
+
+        Ok(lead)
+    }
+
+    /// Like `new`, but also reports the guesses made while normalizing the phone number.
+    /// Numbers that can't be normalized at all are still an error, returned here rather than
+    /// from a separate method: a `LeadDetails` always holds a normalized number, so there is
+    /// no lead to hand back with a warning.
+    pub fn new_lenient(name: String, phone_number: String) -> Result<(LeadDetails, Vec<Warning>), SharedAdapterError> {
+        let (phone_number, warnings) = normalize_phone_with_warnings(phone_number)?;
+
+        Ok((LeadDetails { name, phone_number }, warnings))
+    }
+
+    pub fn phone(&self) -> &str {
+        &self.phone_number
+    }
+
+    /// Reads a lead out of an item using configured column ids, instead of guessing which
+    /// column holds the phone number like the `TryFrom<&Item>` conversion does. The name
+    /// falls back as described on `LeadColumnMap`.
+    pub fn from_item(item: &Item, map: &LeadColumnMap) -> Result<LeadDetails, SharedAdapterError> {
+        let name = map.resolve_name(item).ok_or(SharedAdapterError::DataFieldNotFound { field: "name".to_string(), available: Vec::new() })?;
+        let phone_number = item
+            .column(&map.phone)
+            .and_then(column_phone)
+            .ok_or(SharedAdapterError::DataFieldNotFound { field: map.phone.clone(), available: Vec::new() })?;
+
+        LeadDetails::new(name, phone_number)
+    }
+
+    /// The phone number in E.164 form, which identifies a lead across imports
+    pub fn dedup_key(&self) -> String {
+        format!("+{}", self.phone_number)
+    }
+
+    /// The fields that differ between two leads. Phones are compared in normalized form.
+    pub fn diff(&self, other: &LeadDetails) -> Vec<LeadField> {
+        let mut changed = Vec::new();
+
+        if self.name != other.name {
+            changed.push(LeadField::Name);
+        }
+        if self.phone_number != other.phone_number {
+            changed.push(LeadField::Phone);
+        }
+
+        changed
+    }
+
+    /// Replaces the phone number, running it through the same validation as `new`
+    pub fn set_phone(&mut self, raw: &str) -> Result<(), SharedAdapterError> {
+        self.phone_number = normalize_phone(raw.to_string())?;
+        Ok(())
+    }
+
+    /// Reads the `FN` and `TEL` properties of a vCard, including grouped ones such as
+    /// `item1.TEL` and values folded over several lines. With several `TEL` lines the first
+    /// one typed as a cell phone wins, otherwise the first one listed.
+    pub fn from_vcard(vcard: &str) -> Result<LeadDetails, SharedAdapterError> {
+        let mut name = None;
+        let mut phones = Vec::new();
+
+        // A line starting with a space or tab continues the one before it
+        let mut lines: Vec<String> = Vec::new();
+        for line in vcard.lines() {
+            match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+                (Some(continuation), Some(last)) => last.push_str(continuation),
+                _ => lines.push(line.to_string())
+            }
+        }
+
+        for line in &lines {
+            let Some((property, value)) = line.trim().split_once(':') else {
+                continue;
+            };
+
+            let mut params = property.split(';');
+            let property_name = params.next().unwrap_or_default();
+            // Grouped properties such as `item1.TEL` carry their group before a dot
+            let property_name = property_name.rsplit('.').next().unwrap_or(property_name).to_uppercase();
+
+            match property_name.as_str() {
+                "FN" => name = Some(value.trim().to_string()),
+                "TEL" => {
+                    let is_cell = params
+                        .flat_map(|param| param.split(','))
+                        .any(|param| {
+                            let param = param.to_uppercase();
+                            param == "CELL" || param == "TYPE=CELL"
+                        });
+                    let number = value.trim().trim_start_matches("tel:");
+                    phones.push((is_cell, number));
+                }
+                _ => {}
+            }
+        }
+
+        let name = name.ok_or(SharedAdapterError::DataFieldNotFound { field: "FN".to_string(), available: Vec::new() })?;
+        let (_, phone) = phones
+            .iter()
+            .find(|(is_cell, _)| *is_cell)
+            .or(phones.first())
+            .ok_or(SharedAdapterError::DataFieldNotFound { field: "TEL".to_string(), available: Vec::new() })?;
+
+        LeadDetails::new(name, phone.to_string())
+    }
+}
+
+/// Monday column ids that lead fields are stored in.
+///
+/// A lead's name is resolved in order from the item's own name, the `name_col` column and
+/// finally `name_placeholder`, skipping blank values.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LeadColumnMap {
+    pub phone: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_col: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_placeholder: Option<String>,
+}
+
+impl LeadColumnMap {
+    fn column_ids(&self) -> Vec<&str> {
+        let mut column_ids = vec![self.phone.as_str()];
+        column_ids.extend(self.name_col.as_deref());
+        column_ids
+    }
+
+    fn resolve_name(&self, item: &Item) -> Option<String> {
+        let from_column = self.name_col.as_deref().and_then(|name_col| item.column_text(name_col));
+
+        [item.name.as_deref(), from_column, self.name_placeholder.as_deref()]
+            .into_iter()
+            .flatten()
+            .find(|name| !name.trim().is_empty())
+            .map(|name| name.to_string())
+    }
+
+    /// Checks that every configured column exists on a fetched item, returning the ids
+    /// that don't. Meant to catch typos in the configuration at startup.
+    pub fn validate_against(&self, item: &Item) -> Result<(), Vec<String>> {
+        let missing: Vec<String> = self.column_ids()
+            .into_iter()
+            .filter(|column_id| item.column(column_id).is_none())
+            .map(|column_id| column_id.to_string())
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+}
+
+/// A field of `LeadDetails`, as reported by `LeadDetails::diff`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LeadField {
+    Name,
+    Phone,
+}
+
+/// A recoverable issue found while normalizing a phone number
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Warning {
+    /// A 10 digit number was assumed to be a US number and given a leading `1`
+    AssumedUsPrefix,
+    /// Spaces, dashes, dots, brackets or `+` were removed
+    StrippedFormatting,
+    /// An extension was dropped
+    StrippedExtension,
+}
+
+/// A phone number in E.164 form, e.g. `+15551234567`. Deserializing validates it like
+/// `PhoneNumber::from_e164`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(try_from = "String")]
+pub struct PhoneNumber(String);
+
+impl TryFrom<String> for PhoneNumber {
+    type Error = SharedAdapterError;
+
+    fn try_from(e164: String) -> Result<Self, Self::Error> {
+        PhoneNumber::from_e164(&e164)
+    }
+}
+
+/// How many digits an E.164 number has, not counting the `+`
+const E164_DIGITS: RangeInclusive<usize> = 7..=15;
+
+/// Calling codes recognized by `PhoneNumber::country_code`, with the ISO 3166 country Monday
+/// shows for them. Codes shared by several countries map to the largest one.
+const COUNTRY_CODES: &[(u16, &str)] = &[
+    (1, "US"), (7, "RU"), (20, "EG"), (27, "ZA"), (30, "GR"), (31, "NL"), (32, "BE"), (33, "FR"),
+    (34, "ES"), (39, "IT"), (41, "CH"), (44, "GB"), (45, "DK"), (46, "SE"), (47, "NO"), (48, "PL"),
+    (49, "DE"), (52, "MX"), (54, "AR"), (55, "BR"), (57, "CO"), (61, "AU"), (63, "PH"), (64, "NZ"),
+    (81, "JP"), (82, "KR"), (86, "CN"), (90, "TR"), (91, "IN"), (234, "NG"), (351, "PT"), (353, "IE"),
+    (971, "AE"), (972, "IL")
+];
+
+/// The known calling code a run of digits starts with, and how many digits it takes up
+fn calling_code(digits: &str) -> Option<(u16, usize)> {
+    (1..=3)
+        .rev()
+        .filter_map(|len| Some((digits.get(..len)?.parse::<u16>().ok()?, len)))
+        .find(|(prefix, _)| COUNTRY_CODES.iter().any(|(code, _)| code == prefix))
+}
+
+/// The ISO 3166 country a run of digits starting with a calling code belongs to
+fn country_short_name(digits: &str) -> Option<&'static str> {
+    let (prefix, _) = calling_code(digits)?;
+
+    COUNTRY_CODES.iter().find(|(code, _)| *code == prefix).map(|(_, country)| *country)
+}
+
+impl PhoneNumber {
+    /// Normalizes a number written any way `LeadDetails::new` accepts
+    pub fn parse(raw: &str) -> Result<PhoneNumber, SharedAdapterError> {
+        Ok(PhoneNumber(format!("+{}", normalize_phone(raw.to_string())?)))
+    }
+
+    /// Wraps an already-normalized number: a `+` followed by 7 to 15 digits
+    pub fn from_e164(e164: &str) -> Result<PhoneNumber, SharedAdapterError> {
+        let valid = e164
+            .strip_prefix('+')
+            .is_some_and(|digits| E164_DIGITS.contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit()));
+
+        if !valid {
+            return Err(SharedAdapterError::InvalidPhoneNumber(e164.to_string()));
+        }
+
+        Ok(PhoneNumber(e164.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The calling code the number starts with, matching the longest known prefix
+    pub fn country_code(&self) -> Option<u16> {
+        calling_code(self.0.trim_start_matches('+')).map(|(code, _)| code)
+    }
+
+    /// The number as shown in US national format, `(555) 123-4567`. Numbers outside
+    /// country code 1 are returned in E.164 form unchanged.
+    pub fn to_national(&self) -> String {
+        match self.0.strip_prefix("+1") {
+            Some(national) if national.len() == 10 && national.bytes().all(|b| b.is_ascii_digit()) => {
+                format!("({}) {}-{}", &national[..3], &national[3..6], &national[6..])
+            }
+            _ => self.0.clone()
+        }
+    }
+}
+
+impl fmt::Display for PhoneNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Hashes only the normalized phone number, so formatting variants of the same number
+/// land in the same bucket. Still consistent with `Eq`, which also compares the phone.
+impl Hash for LeadDetails {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.phone_number.hash(state);
+    }
+}
+
+impl PartialOrd for LeadDetails {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders by normalized phone number, then by name
+impl Ord for LeadDetails {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.phone_number
+            .cmp(&other.phone_number)
+            .then_with(|| self.name.cmp(&other.name))
+    }
+}
+
+/// Masks phone numbers anywhere in a JSON document so it can be logged. Strings of 7 to 15
+/// digits, optionally formatted, keep only their last 4 digits; numbers of that length become
+/// masked strings, the only change to the document's shape. Values under an `id` or `*_id`
+/// key are left alone since Monday ids look like numbers too, ISO dates such as `2024-06-01`
+/// are kept, and JSON-encoded strings such as a column's `value` are redacted inside.
+pub fn redact_phones_in_json(value: &mut serde_json::Value) {
+    redact_phones(value);
+}
+
+/// Does the work of `redact_phones_in_json`, returning whether anything was masked so that
+/// JSON-encoded strings are only re-encoded when they changed
+fn redact_phones(value: &mut serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(map) => map
+            .iter_mut()
+            .filter(|(key, _)| *key != "id" && !key.ends_with("_id"))
+            .fold(false, |masked, (_, value)| redact_phones(value) | masked),
+        serde_json::Value::Array(values) => values.iter_mut().fold(false, |masked, value| redact_phones(value) | masked),
+        serde_json::Value::String(text) => {
+            if let Ok(mut nested @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) = serde_json::from_str(text) {
+                let masked = redact_phones(&mut nested);
+                if masked {
+                    *text = nested.to_string();
+                }
+                masked
+            } else if is_phone_like(text) {
+                *text = mask_digits(text);
+                true
+            } else {
+                false
+            }
+        }
+        serde_json::Value::Number(number) => {
+            let Some(digits) = number.as_u64().map(|n| n.to_string()).filter(|digits| is_phone_like(digits)) else {
+                return false;
+            };
+            *value = serde_json::Value::String(mask_digits(&digits));
+            true
+        }
+        _ => false
+    }
+}
+
+fn is_phone_like(text: &str) -> bool {
+    let digits = text.chars().filter(|c| c.is_ascii_digit()).count();
+
+    (7..=15).contains(&digits)
+        && !is_iso_date(text)
+        && text.chars().all(|c| c.is_ascii_digit() || matches!(c, ' ' | '-' | '.' | '(' | ')' | '+'))
+}
+
+/// `YYYY-MM-DD`, the shape Monday date columns use
+fn is_iso_date(text: &str) -> bool {
+    text.len() == 10
+        && text.bytes().enumerate().all(|(i, b)| match i {
+            4 | 7 => b == b'-',
+            _ => b.is_ascii_digit()
+        })
+}
+
+fn mask_digits(text: &str) -> String {
+    let mut to_mask = text.chars().filter(|c| c.is_ascii_digit()).count().saturating_sub(4);
+
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_digit() && to_mask > 0 {
+                to_mask -= 1;
+                '*'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Tries `LeadDetails::new` on every `(name, phone)` pair so a whole import can be checked
+/// at once. Failures carry the index of the input they came from.
+pub fn validate_lead_inputs(inputs: &[(String, String)]) -> Vec<Result<LeadDetails, (usize, SharedAdapterError)>> {
+    inputs
+        .iter()
+        .enumerate()
+        .map(|(i, (name, phone_number))| LeadDetails::new(name.clone(), phone_number.clone()).map_err(|error| (i, error)))
+        .collect()
+}
+
+/// Drops leads whose `dedup_key` was already seen, keeping the first occurrence
+pub fn dedup_leads(leads: Vec<LeadDetails>) -> Vec<LeadDetails> {
+    let mut seen = HashSet::new();
+    leads.into_iter().filter(|lead| seen.insert(lead.dedup_key())).collect()
+}
+
+/// Sorts leads alphabetically by name, ignoring case, then by phone number
+pub fn sort_leads_by_name(leads: &mut [LeadDetails]) {
+    leads.sort_by(|a, b| {
+        a.name.to_lowercase()
+            .cmp(&b.name.to_lowercase())
+            .then_with(|| a.phone_number.cmp(&b.phone_number))
+    });
+}
+
+/// A phone number as the user typed it, alongside its canonical form
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct NormalizedPhone {
+    pub original: String,
+    pub e164: String,
+}
+
+/// Normalizes a phone number like `LeadDetails::new` does, keeping the original input for auditing
+pub fn normalize_phone_detailed(raw: &str) -> Result<NormalizedPhone, SharedAdapterError> {
+    let phone_number = normalize_phone(raw.to_string())?;
+
+    Ok(NormalizedPhone {
+        original: raw.to_string(),
+        e164: format!("+{}", phone_number)
+    })
+}
+
+/// Phone numbers that must never be messaged, see `LeadDetails::new_checked`. Entries are
+/// normalized once when the list is built, so lookups don't depend on how they were written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Blocklist(HashSet<String>);
+
+impl Blocklist {
+    /// Normalizes every entry like `LeadDetails::new`. Entries that aren't phone numbers fail
+    /// the whole list, with the index of each one, rather than leaving a number unblocked.
+    pub fn new(entries: impl IntoIterator<Item = String>) -> Result<Blocklist, Vec<(usize, SharedAdapterError)>> {
+        let mut blocked = HashSet::new();
+        let mut errors = Vec::new();
+
+        for (i, entry) in entries.into_iter().enumerate() {
+            match normalize_phone(entry) {
+                Ok(phone_number) => {
+                    blocked.insert(phone_number);
+                }
+                Err(error) => errors.push((i, error))
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(Blocklist(blocked))
+    }
+
+    /// Whether a phone number, in any format `LeadDetails::new` accepts, is on the list
+    pub fn contains(&self, phone_number: &str) -> bool {
+        normalize_phone(phone_number.to_string()).is_ok_and(|phone_number| self.0.contains(&phone_number))
+    }
+}
+
+fn normalize_phone(raw: String) -> Result<String, SharedAdapterError> {
+    normalize_phone_with_warnings(raw).map(|(phone_number, _)| phone_number)
+}
+
+fn normalize_phone_with_warnings(raw: String) -> Result<(String, Vec<Warning>), SharedAdapterError> {
+    let mut warnings = Vec::new();
+
+    // Extensions can't be dialed from WhatsApp, so they are dropped rather than rejected
+    let (main_number, extension) = split_extension(&raw);
+    if extension.is_some() {
+        warnings.push(Warning::StrippedExtension);
+    }
+
+    // Only digits and common formatting characters are allowed in the main number
+    if !main_number.chars().all(|c| c.is_ascii_digit() || matches!(c, ' ' | '-' | '.' | '(' | ')' | '+')) {
+        return Err(SharedAdapterError::InvalidPhoneNumber(raw));
+    }
+
+    let mut phone_number: String = main_number.chars().filter(|c| c.is_ascii_digit()).collect();
+    if phone_number.len() != main_number.len() {
+        warnings.push(Warning::StrippedFormatting);
+    }
+
+    // A leading `+`, or the 00 international dialing prefix written in its place, means the
+    // number already carries its country code, so the US rules below don't apply
+    let international = match phone_number.strip_prefix("00") {
+        Some(international) => Some(international.to_string()),
+        None if main_number.starts_with('+') => Some(phone_number.clone()),
+        None => None
+    };
+
+    if let Some(mut international) = international {
+        // A national trunk 0 kept after the country code, as in `+44 (0)20 ...`, isn't
+        // dialed from abroad. Italian numbers are the exception and keep their 0.
+        if let Some((code, len)) = calling_code(&international) {
+            if code != 39 && international[len..].starts_with('0') {
+                international.remove(len);
+            }
+        }
+
+        if !E164_DIGITS.contains(&international.len()) {
+            return Err(SharedAdapterError::InvalidPhoneNumber(raw));
+        }
+        return Ok((international, warnings));
+    }
+
+    // Ensure that the phone number is either 10 or 11 digits
+    if phone_number.len() != 10 && phone_number.len() != 11 {
+        return Err(SharedAdapterError::InvalidPhoneNumber(raw));
+    }
+
+    // If the phone number is 10 digits, add a '1' to the beginning
+    if phone_number.len() == 10 {
+        phone_number.insert(0, '1');
+        warnings.push(Warning::AssumedUsPrefix);
+    }
+
+    Ok((phone_number, warnings))
+}
+
+/// Whether the 10 digit national part of a normalized number is a single repeated digit
+/// or a run of consecutive digits, up or down
+fn is_suspicious_phone(phone_number: &str) -> bool {
+    let national: Vec<u8> = phone_number
+        .bytes()
+        .skip(phone_number.len().saturating_sub(10))
+        .map(|digit| digit - b'0')
+        .collect();
+
+    let steps_by = |step: u8| national.windows(2).all(|pair| pair[1] == (pair[0] + step) % 10);
+
+    steps_by(0) || steps_by(1) || steps_by(9)
+}
+
+/// Splits `"555-123-4567 ext. 890"` into the main number and the extension digits.
+/// `ext`, `x` and `#` are recognized as extension markers.
+fn split_extension(raw: &str) -> (&str, Option<String>) {
+    let lowercase = raw.to_ascii_lowercase();
+    let marker = lowercase
+        .char_indices()
+        .find(|(i, c)| *c == 'x' || *c == '#' || lowercase[*i..].starts_with("ext"));
+
+    match marker {
+        Some((i, _)) => {
+            let extension: String = raw[i..].chars().filter(|c| c.is_ascii_digit()).collect();
+            (raw[..i].trim(), Some(extension).filter(|extension| !extension.is_empty()))
+        }
+        None => (raw.trim(), None)
+    }
+}
+
+impl TryFrom<ItemsPage> for LeadDetails {
+    type Error = SharedAdapterError;
+
+    fn try_from(items_page: ItemsPage) -> Result<Self, Self::Error> {
+        let item = items_page.items.first().ok_or(SharedAdapterError::DataFieldNotFound { field: "items".to_string(), available: Vec::new() })?;
+
+        LeadDetails::try_from(item)
+    }
+}
+
+impl TryFrom<&Item> for LeadDetails {
+    type Error = SharedAdapterError;
+
+    fn try_from(item: &Item) -> Result<Self, Self::Error> {
+        let name = item.name.clone().ok_or(SharedAdapterError::DataFieldNotFound { field: "name".to_string(), available: Vec::new() })?;
+        // Items fetched without column ids have no `column_values`, which is the same as none
+        let column_values = item.column_values.as_deref().unwrap_or_default();
+
+        // Empty columns come back with a `null` text and are skipped. Any column holding enough
+        // digits would normalize, so columns marked as a phone are tried before the rest
+        let phone_number = column_values
+            .iter()
+            .filter_map(|column_value| {
+                let phone_number = normalize_phone(column_text(column_value)?.to_string()).ok()?;
+                Some((phone_column_rank(column_value), phone_number))
+            })
+            .min_by_key(|(rank, _)| *rank)
+            .map(|(_, phone_number)| phone_number)
+            .ok_or_else(|| SharedAdapterError::DataFieldNotFound {
+                field: "phone_number".to_string(),
+                available: column_ids(column_values)
+            })?;
+
+        Ok(LeadDetails {
+            name,
+            phone_number
+        })
+    }
+}
+
+impl TryFrom<HashMap<String, String>> for LeadDetails {
+    type Error = SharedAdapterError;
+
+    /// Reads a flat form submission with `name` and `phone` keys. `phone_number` is
+    /// accepted in place of `phone`.
+    fn try_from(mut fields: HashMap<String, String>) -> Result<Self, Self::Error> {
+        let name = fields.remove("name").ok_or(SharedAdapterError::DataFieldNotFound { field: "name".to_string(), available: Vec::new() })?;
+        let phone_number = fields
+            .remove("phone")
+            .or_else(|| fields.remove("phone_number"))
+            .ok_or(SharedAdapterError::DataFieldNotFound { field: "phone".to_string(), available: Vec::new() })?;
+
+        LeadDetails::new(name, phone_number)
+    }
+}
+
+/// The display text of a raw column value, taken from the first non-empty of:
+/// 1. `text`
+/// 2. `display_value`, where mirror and linked-item columns put their contents
+fn column_text(column_value: &HashMap<String, serde_json::Value>) -> Option<&str> {
+    ["text", "display_value"]
+        .iter()
+        .find_map(|key| column_value.get(*key)?.as_str().filter(|text| !text.is_empty()))
+}
+
+/// The parsed `value` of a raw column. Monday sends it as a JSON-encoded string.
+fn column_json_value(column_value: &HashMap<String, serde_json::Value>) -> Option<serde_json::Value> {
+    match column_value.get("value")? {
+        serde_json::Value::String(encoded) => serde_json::from_str(encoded).ok(),
+        serde_json::Value::Null => None,
+        value => Some(value.clone())
+    }
+}
+
+/// The phone number held in a raw column: its display text, or else its `value`. Mirrored
+/// phone columns can leave the text empty and send the number as a JSON number, either
+/// bare or under `phone`, so numbers are turned back into their digits.
+fn column_phone(column_value: &HashMap<String, serde_json::Value>) -> Option<String> {
+    if let Some(text) = column_text(column_value) {
+        return Some(text.to_string());
+    }
+
+    let value = column_json_value(column_value)?;
+    let phone = value.get("phone").unwrap_or(&value);
+    match phone {
+        serde_json::Value::String(phone) if !phone.is_empty() => Some(phone.clone()),
+        serde_json::Value::Number(phone) => phone.as_u64().map(|digits| digits.to_string()),
+        _ => None
+    }
+}
+
+/// How likely a raw column is to hold the lead's phone, lowest first: a column whose type or id
+/// says phone, then one whose text is written with a `+`, then anything else.
+fn phone_column_rank(column_value: &HashMap<String, serde_json::Value>) -> u8 {
+    let marked_as_phone = ["id", "type"].iter().any(|key| {
+        column_value
+            .get(*key)
+            .and_then(serde_json::Value::as_str)
+            .is_some_and(|marker| marker.to_ascii_lowercase().contains("phone"))
+    });
+
+    if marked_as_phone {
+        0
+    } else if column_text(column_value).is_some_and(|text| text.trim_start().starts_with('+')) {
+        1
+    } else {
+        2
+    }
+}
+
+fn column_id(column_value: &HashMap<String, serde_json::Value>) -> Option<&str> {
+    column_value.get("id")?.as_str()
+}
+
+fn column_ids(column_values: &[HashMap<String, serde_json::Value>]) -> Vec<String> {
+    column_values
+        .iter()
+        .filter_map(column_id)
+        .map(|id| id.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn update(recipient_id: &str, status: MessageStatus) -> StatusUpdate {
+        StatusUpdate {
+            recipient_id: recipient_id.to_string(),
+            status,
+            error: None,
+            message_id: None
+        }
+    }
+
+    fn lead(name: &str, phone_number: &str) -> LeadDetails {
+        LeadDetails::new(name.to_string(), phone_number.to_string()).unwrap()
+    }
+
+    fn page(json: serde_json::Value) -> ItemsPage {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn raw_column(json: serde_json::Value) -> HashMap<String, serde_json::Value> {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn appointment_map() -> AppointmentColumnMap {
+        AppointmentColumnMap {
+            phone: "phone".to_string(),
+            availability: "text1".to_string(),
+            notes: "long_text".to_string(),
+            date: "date4".to_string()
+        }
+    }
+
+    #[test]
+    fn set_phone_revalidates() {
+        let mut lead = lead("Jane", "5551234567");
+
+        lead.set_phone("15559876543").unwrap();
+        assert_eq!(lead.phone(), "15559876543");
+
+        assert!(matches!(lead.set_phone("123"), Err(SharedAdapterError::InvalidPhoneNumber(_))));
+        assert_eq!(lead.phone(), "15559876543");
+    }
+
+    #[test]
+    fn deserializing_validates_the_phone() {
+        let parsed: LeadDetails = serde_json::from_value(json!({ "name": "Jane", "phone_number": "(555) 123-4567" })).unwrap();
+        assert_eq!(parsed.phone(), "15551234567");
+
+        assert!(serde_json::from_value::<LeadDetails>(json!({ "name": "Jane", "phone_number": "not a phone" })).is_err());
+
+        let bare: LeadDetails = serde_json::from_value(json!({ "name": "Jane", "phone_number": "5551234567" })).unwrap();
+        assert_eq!(bare, lead("Jane", "5551234567"));
+
+        let international = lead("Jo", "+44 20 7946 0958");
+        assert_eq!(serde_json::to_value(&international).unwrap()["phone_number"], "+442079460958");
+        let round_trip: LeadDetails = serde_json::from_value(serde_json::to_value(&international).unwrap()).unwrap();
+        assert_eq!(round_trip, international);
+    }
+
+    #[test]
+    fn reconciler_ignores_out_of_order_updates() {
+        let mut reconciler = StatusReconciler::new();
+        for (recipient, status) in [
+            ("a", MessageStatus::Delivered),
+            ("b", MessageStatus::Sent),
+            ("a", MessageStatus::Sent),
+            ("b", MessageStatus::Failed),
+            ("b", MessageStatus::Read),
+        ] {
+            reconciler.record(update(recipient, status));
+        }
+
+        assert_eq!(reconciler.snapshot()["a"], MessageStatus::Delivered);
+        assert_eq!(reconciler.snapshot()["b"], MessageStatus::Failed);
+    }
+
+    #[test]
+    fn missing_phone_lists_available_columns() {
+        let page = page(json!({ "items": [{ "name": "x", "id": "1", "column_values": [
+            { "id": "name", "text": "bob" },
+            { "id": "email", "text": "a@b" }
+        ] }] }));
+
+        let error = LeadDetails::try_from(page).unwrap_err();
+        assert_eq!(error.to_string(), "Data field not found: phone_number (available: name, email)");
+    }
+
+    #[test]
+    fn lead_flattens_into_a_wrapper() {
+        #[derive(Serialize, Deserialize)]
+        struct Wrapper {
+            #[serde(flatten)]
+            lead: LeadDetails,
+            message: String,
+        }
+
+        let wrapper = Wrapper { lead: lead("a", "5551234567"), message: "hi".to_string() };
+        let value = serde_json::to_value(&wrapper).unwrap();
+        assert_eq!(value, json!({ "name": "a", "phone_number": "+15551234567", "message": "hi" }));
+
+        let wrapper: Wrapper = serde_json::from_value(value).unwrap();
+        assert_eq!(wrapper.lead.phone(), "15551234567");
+    }
+
+    #[test]
+    fn into_leads_skips_bad_items() {
+        let page = page(json!({ "items": [
+            { "name": "x", "id": "1", "column_values": [{ "id": "phone", "text": "15551234567" }] },
+            { "name": "y", "id": "2" }
+        ] }));
+
+        let mut leads = page.into_leads();
+        assert_eq!(leads.by_ref().count(), 1);
+        assert_eq!(leads.skipped_count(), 1);
+    }
+
+    #[test]
+    fn column_value_from_raw() {
+        let column = ColumnValue::from_raw(&raw_column(json!({ "id": "phone", "text": "1555", "value": null }))).unwrap();
+        assert_eq!(column.id, "phone");
+        assert_eq!(column.text.as_deref(), Some("1555"));
+        assert!(column.value.is_none());
+
+        assert!(ColumnValue::from_raw(&raw_column(json!({ "text": "1555" }))).is_err());
+    }
+
+    #[test]
+    fn semantic_equality_ignores_availability_order() {
+        let a = AppointmentRequest {
+            availabilities: vec![AvailableTime::Morning, AvailableTime::Evening],
+            ..Default::default()
+        };
+        let b = AppointmentRequest {
+            availabilities: vec![AvailableTime::Evening, AvailableTime::Morning, AvailableTime::Evening],
+            ..Default::default()
+        };
+
+        assert!(a.semantically_eq(&b));
+    }
+
+    #[test]
+    fn webhook_captures_failure_codes() {
+        let payload = json!({ "entry": [{ "changes": [{ "value": { "statuses": [
+            { "id": "wamid.1", "status": "failed", "recipient_id": "15551234567", "errors": [{ "code": 131026, "title": "Message undeliverable" }] },
+            { "id": "wamid.2", "status": "delivered", "recipient_id": "15551234568" }
+        ] } }] }] });
+
+        let updates = StatusUpdate::from_whatsapp_webhook(&payload);
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].error, Some(StatusError { code: 131026, title: "Message undeliverable".to_string() }));
+        assert_eq!(updates[0].message_id.as_deref(), Some("wamid.1"));
+        assert_eq!(updates[1].status, MessageStatus::Delivered);
+        assert!(updates[1].error.is_none());
+    }
+
+    #[test]
+    fn status_counts_include_every_status() {
+        let counts = HashMap::from([(MessageStatus::Sent, 10)]);
+        let json = status_counts_to_json(&counts);
+
+        assert_eq!(json.as_object().unwrap().len(), MessageStatus::all().len());
+        assert_eq!(json["sent"], 10);
+        assert_eq!(json["not sent"], 0);
+    }
+
+    #[test]
+    fn monday_item_puts_the_date_in_the_date_column() {
+        let mut request = AppointmentRequest {
+            phone_number: "4155551234".to_string(),
+            availabilities: vec![AvailableTime::Morning, AvailableTime::Evening],
+            requested_date: "2024-06-01".to_string(),
+            ..Default::default()
+        };
+
+        let (name, columns) = request.to_monday_item(&appointment_map()).unwrap();
+        assert_eq!(name, "4155551234");
+        assert_eq!(columns["phone"], json!({ "phone": "+14155551234", "countryShortName": "US" }));
+        assert_eq!(columns["date4"], json!({ "date": "2024-06-01" }));
+        assert_eq!(columns["text1"], "morning, evening");
+
+        request.phone_number = "12".to_string();
+        assert!(matches!(request.to_monday_item(&appointment_map()), Err(SharedAdapterError::InvalidPhoneNumber(_))));
+    }
+
+    #[test]
+    fn vcard_import() {
+        let minimal = LeadDetails::from_vcard("BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Jane Doe\r\nTEL:555-123-4567\r\nEND:VCARD\r\n").unwrap();
+        assert_eq!(minimal.name, "Jane Doe");
+        assert_eq!(minimal.phone(), "15551234567");
+
+        let multi_phone = LeadDetails::from_vcard("BEGIN:VCARD\nFN:J\nTEL;TYPE=work,voice:+1 555 000 1111\nTEL;TYPE=cell:(555) 222-3333\nEND:VCARD").unwrap();
+        assert_eq!(multi_phone.phone(), "15552223333");
+
+        let international = LeadDetails::from_vcard("BEGIN:VCARD\nFN:J\nTEL:+65 6123 4567\nEND:VCARD").unwrap();
+        assert_eq!(international.phone(), "6561234567");
+
+        let grouped = LeadDetails::from_vcard("BEGIN:VCARD\r\nFN:J\r\nitem1.TEL;TYPE=cell:+44 20 79\r\n 46 0958\r\nitem1.X-ABLabel:mobile\r\nEND:VCARD\r\n").unwrap();
+        assert_eq!(grouped.phone(), "442079460958");
+
+        assert!(LeadDetails::from_vcard("BEGIN:VCARD\nFN:J\nEND:VCARD").is_err());
+    }
+
+    #[test]
+    fn extensions_are_stripped() {
+        assert_eq!(lead("a", "5551234567 x123").phone(), "15551234567");
+        assert_eq!(lead("a", "555.123.4567 ext. 4").phone(), "15551234567");
+        assert!(LeadDetails::new("a".to_string(), "555abc4567".to_string()).is_err());
+    }
+
+    #[test]
+    fn sort_by_name_breaks_ties_by_phone() {
+        let mut leads = vec![lead("bob", "5550000001"), lead("alice", "5550000003"), lead("Alice", "5550000002")];
+        sort_leads_by_name(&mut leads);
+
+        let names: Vec<&str> = leads.iter().map(|lead| lead.name.as_str()).collect();
+        assert_eq!(names, ["Alice", "alice", "bob"]);
+    }
+
+    #[test]
+    fn mirror_columns_expose_display_value() {
+        let page = page(json!({ "items": [{ "name": "x", "id": "1", "column_values": [
+            { "id": "mirror", "text": "", "display_value": "15551234567" }
+        ] }] }));
+
+        assert_eq!(LeadDetails::try_from(page).unwrap().phone(), "15551234567");
+    }
+
+    #[test]
+    fn sanitized_notes_keep_characters_whole() {
+        let mut request = AppointmentRequest {
+            additional_information: "héllo\u{7}wörld".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(request.sanitized_notes(5), "héll…");
+        assert_eq!(request.sanitized_notes(100), "héllowörld");
+
+        request.additional_information = "日本語です".to_string();
+        assert_eq!(request.sanitized_notes(3), "日本…");
+    }
+
+    #[test]
+    fn pages_collect_from_items() {
+        let page: ItemsPage = (0..3).map(|id| ItemBuilder::new().id(&id.to_string()).build()).collect();
+
+        assert_eq!(page.len(), 3);
+        assert!(page.cursor().is_none());
+    }
+
+    #[test]
+    fn formatting_variants_dedup_to_one_lead() {
+        let leads = dedup_leads(vec![lead("a", "(555) 123-4567"), lead("b", "1-555-123-4567")]);
+
+        assert_eq!(leads.len(), 1);
+        assert_eq!(leads[0].name, "a");
+    }
+
+    #[test]
+    fn availabilities_accept_one_or_many() {
+        let scalar: AppointmentRequest = serde_json::from_value(json!({ "phone_number": "1", "availabilities": "morning", "requested_date": "d" })).unwrap();
+        assert_eq!(scalar.availabilities, vec![AvailableTime::Morning]);
+
+        let array: AppointmentRequest = serde_json::from_value(json!({ "phone_number": "1", "availabilities": ["morning", "evening"], "requested_date": "d" })).unwrap();
+        assert_eq!(array.availabilities, vec![AvailableTime::Morning, AvailableTime::Evening]);
+    }
+
+    #[test]
+    fn country_code_inference() {
+        assert_eq!(PhoneNumber::from_e164("+15551234567").unwrap().country_code(), Some(1));
+        assert_eq!(PhoneNumber::from_e164("+442079460958").unwrap().country_code(), Some(44));
+        assert_eq!(PhoneNumber::from_e164("+999123456789").unwrap().country_code(), None);
+        assert!(PhoneNumber::from_e164("15551234567").is_err());
+    }
+
+    #[test]
+    fn phone_number_deserializing_validates() {
+        let phone: PhoneNumber = serde_json::from_value(json!("+15551234567")).unwrap();
+        assert_eq!(phone.as_str(), "+15551234567");
+
+        assert!(serde_json::from_value::<PhoneNumber>(json!("+1ééééé")).is_err());
+        assert!(serde_json::from_value::<PhoneNumber>(json!("15551234567")).is_err());
+    }
+
+    #[test]
+    fn merged_pages_keep_the_last_cursor() {
+        let first = page(json!({ "items": [{ "id": "1" }], "cursor": "c1" }));
+        let second = page(json!({ "items": [{ "id": "2" }, { "id": "3" }], "cursor": "c2" }));
+
+        let merged = merge_pages(vec![first, second]);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged.cursor(), Some("c2"));
+    }
+
+    #[test]
+    fn webhook_recipient_matches_lead() {
+        let update = update("15551234567", MessageStatus::Sent);
+
+        assert_eq!(update.normalized_recipient().unwrap(), lead("a", "(555) 123-4567").dedup_key());
+    }
+
+    #[test]
+    fn recipients_keep_their_country_code() {
+        assert_eq!(update("447911123456", MessageStatus::Sent).normalized_recipient().unwrap(), "+447911123456");
+
+        let singapore = update("6591234567", MessageStatus::Sent);
+        assert_eq!(singapore.normalized_recipient().unwrap(), "+6591234567");
+        assert_eq!(singapore.normalized_recipient().unwrap(), singapore.to_phone_number().unwrap().as_str());
+    }
+
+    #[test]
+    fn binary_encoding_round_trips() {
+        let update = update("15551234567", MessageStatus::Read);
+        let bytes = update.to_bytes();
+
+        let decoded = StatusUpdate::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.recipient_id, update.recipient_id);
+        assert_eq!(decoded.status, update.status);
+
+        assert!(StatusUpdate::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(StatusUpdate::from_bytes(&[]).is_err());
+    }
+
+    #[test]
+    fn page_item_limit() {
+        let json = r#"{"items":[{"id":"1"},{"id":"2"},{"id":"3"}],"cursor":"c"}"#;
+
+        assert!(matches!(ItemsPage::from_json_limited(json, 2), Err(SharedAdapterError::TooManyItems(2))));
+        assert_eq!(ItemsPage::from_json_limited(json, 3).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn lenient_lead_reports_assumed_prefix() {
+        let (lead, warnings) = LeadDetails::new_lenient("a".to_string(), "5551234567".to_string()).unwrap();
+
+        assert_eq!(lead.phone(), "15551234567");
+        assert_eq!(warnings, vec![Warning::AssumedUsPrefix]);
+    }
+
+    #[test]
+    fn people_column_assignees() {
+        let page = page(json!({ "items": [{ "name": "x", "id": "1", "column_values": [
+            { "id": "people", "text": "A, B", "value": "{\"personsAndTeams\":[{\"id\":11,\"kind\":\"person\"},{\"id\":22,\"kind\":\"person\"}]}" }
+        ] }] }));
+
+        assert_eq!(page.items()[0].assignee_ids("people"), vec![11, 22]);
+        assert!(page.items()[0].assignee_ids("missing").is_empty());
+    }
+
+    #[test]
+    fn appointments_from_page() {
+        let first = ItemBuilder::new().name("A").column("phone", "15551234567").column("date4", "2024-06-01").column("text1", "morning, evening").build();
+        let second = ItemBuilder::new().name("B").column("phone", "15551234568").column("date4", "2024-06-02").build();
+        let page: ItemsPage = [first, second].into_iter().collect();
+
+        let (requests, errors) = AppointmentRequest::all_from_page(&page, &appointment_map());
+        assert!(errors.is_empty());
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].availabilities, vec![AvailableTime::Morning, AvailableTime::Evening]);
+        assert_eq!(requests[1].requested_date, "2024-06-02");
+    }
+
+    #[test]
+    fn deduper_recognizes_duplicates() {
+        let event = |message_id: &str, status| StatusUpdate {
+            message_id: Some(message_id.to_string()),
+            ..update("1", status)
+        };
+        let mut deduper = StatusDeduper::new(2);
+
+        assert!(deduper.is_new(&event("a", MessageStatus::Sent)));
+        assert!(!deduper.is_new(&event("a", MessageStatus::Sent)));
+        assert!(deduper.is_new(&event("a", MessageStatus::Delivered)));
+    }
+
+    #[test]
+    fn deduper_evicts_the_least_recently_seen() {
+        let event = |message_id: &str| StatusUpdate {
+            message_id: Some(message_id.to_string()),
+            ..update("1", MessageStatus::Sent)
+        };
+        let mut deduper = StatusDeduper::new(2);
+
+        assert!(deduper.is_new(&event("a")));
+        assert!(deduper.is_new(&event("b")));
+        for _ in 0..10 {
+            assert!(!deduper.is_new(&event("a")));
+        }
+        assert!(deduper.order.len() <= 4);
+
+        assert!(deduper.is_new(&event("c")));
+        assert!(!deduper.is_new(&event("a")));
+        assert!(deduper.is_new(&event("b")));
+    }
+
+    #[test]
+    fn default_appointment_is_empty() {
+        let request = AppointmentRequest::default();
+
+        assert!(request.availabilities.is_empty());
+        assert!(request.name.is_none());
+    }
+
+    #[test]
+    fn diff_reports_only_the_name() {
+        assert_eq!(lead("a", "(555) 123-4567").diff(&lead("b", "15551234567")), vec![LeadField::Name]);
+    }
+
+    #[test]
+    fn histogram_bars_scale_with_counts() {
+        let request = |availabilities| AppointmentRequest { availabilities, ..Default::default() };
+        let requests = [
+            request(vec![AvailableTime::Morning]),
+            request(vec![AvailableTime::Morning, AvailableTime::Evening]),
+        ];
+
+        let histogram = availability_histogram(&requests);
+        let lines: Vec<&str> = histogram.lines().collect();
+        assert!(lines[0].starts_with("morning"));
+        assert_eq!(lines[0].matches('█').count(), HISTOGRAM_WIDTH);
+        assert_eq!(lines[2].matches('█').count(), HISTOGRAM_WIDTH / 2);
+    }
+
+    #[test]
+    fn blocklist_matches_other_formats() {
+        for entry in ["+15551234567", "(555) 123-4567", "+1 (555) 123-4567"] {
+            let blocklist = Blocklist::new([entry.to_string()]).unwrap();
+
+            assert!(blocklist.contains("555.123.4567"));
+            let blocked = LeadDetails::new_checked("a".to_string(), "5551234567".to_string(), &blocklist);
+            assert!(matches!(blocked, Err(SharedAdapterError::BlockedPhoneNumber(_))), "{}", entry);
+        }
+
+        let errors = Blocklist::new(["5551234567".to_string(), "not a phone".to_string()]).unwrap_err();
+        assert!(matches!(errors.as_slice(), [(1, SharedAdapterError::InvalidPhoneNumber(_))]));
+
+        let blocklist = Blocklist::new(["+1 (555) 123-4567".to_string()]).unwrap();
+        let blocked = LeadDetails::new_checked("a".to_string(), "5551234567".to_string(), &blocklist);
+        assert!(matches!(blocked, Err(SharedAdapterError::BlockedPhoneNumber(_))));
+        assert!(LeadDetails::new_checked("a".to_string(), "5551234568".to_string(), &blocklist).is_ok());
+    }
+
+    #[test]
+    fn group_id_deserializes() {
+        let item: Item = serde_json::from_value(json!({ "id": "1", "group": { "id": "topics" } })).unwrap();
+        assert_eq!(item.group_id(), Some("topics"));
+    }
+
+    #[test]
+    fn leads_order_by_phone_then_name() {
+        let mut leads = [lead("b", "5550000002"), lead("z", "5550000001"), lead("a", "5550000002")];
+        leads.sort();
+
+        let names: Vec<&str> = leads.iter().map(|lead| lead.name.as_str()).collect();
+        assert_eq!(names, ["z", "a", "b"]);
+    }
+
+    #[test]
+    fn whatsapp_provider_round_trip() {
+        let provider = WhatsappProvider;
+
+        let outbound = provider.format_outbound(&OutboundMessage { to: "15551234567".to_string(), body: "hi".to_string() });
+        assert_eq!(outbound["to"], "15551234567");
+        assert_eq!(outbound["text"]["body"], "hi");
+
+        let webhook = json!({ "entry": [{ "changes": [{ "value": { "statuses": [{ "id": "w", "status": "read", "recipient_id": "15551234567" }] } }] }] });
+        assert_eq!(provider.parse_status(&webhook)[0].recipient_id, "15551234567");
+    }
+
+    #[test]
+    fn opt_out_keywords() {
+        assert!(detect_opt_out("stop"));
+        assert!(detect_opt_out(" STOP "));
+        assert!(!detect_opt_out("see you tomorrow"));
+    }
+
+    #[test]
+    fn minimal_appointment_payload() {
+        let request: AppointmentRequest = serde_json::from_str(r#"{"phone_number":"1","requested_date":"d"}"#).unwrap();
+
+        assert!(request.name.is_none());
+        assert!(request.availabilities.is_empty());
+        assert!(request.additional_information.is_empty());
+    }
+
+    #[test]
+    fn strict_rejects_fake_numbers() {
+        assert!(matches!(
+            LeadDetails::new_strict("a".to_string(), "0000000000".to_string()),
+            Err(SharedAdapterError::SuspiciousPhoneNumber(_))
+        ));
+        assert!(LeadDetails::new_strict("a".to_string(), "5551234567".to_string()).is_ok());
+    }
+
+    #[test]
+    fn redaction_masks_phones_but_not_ids() {
+        let mut json = json!({ "items": [{ "id": "1234567890", "name": "x", "column_values": [
+            { "id": "phone", "text": "+1 555-123-4567", "value": "{\"phone\":\"15551234567\",\"countryShortName\":\"US\"}" },
+            { "id": "num", "text": "42" },
+            { "id": "date", "text": "2024-06-01", "value": "{\"date\":\"2024-06-01\"}" },
+            { "id": "raw", "text": "", "value": "{\"phone\":15551234567}", "count": 42 },
+            { "id": "other", "text": "x", "value": "{\"a\": 1, \"b\":\"x\"}" }
+        ], "board_id": 1234567890, "pulse_id": "1234567890" }] });
+        redact_phones_in_json(&mut json);
+
+        let columns = &json["items"][0]["column_values"];
+        assert_eq!(columns[0]["text"], "+* ***-***-4567");
+        assert_eq!(columns[0]["value"], "{\"countryShortName\":\"US\",\"phone\":\"*******4567\"}");
+        assert_eq!(json["items"][0]["id"], "1234567890");
+        assert_eq!(columns[1]["text"], "42");
+        assert_eq!(columns[2]["text"], "2024-06-01");
+        assert_eq!(columns[2]["value"], "{\"date\":\"2024-06-01\"}");
+        assert_eq!(columns[3]["value"], "{\"phone\":\"*******4567\"}");
+        assert_eq!(columns[3]["count"], 42);
+        assert_eq!(columns[4]["value"], "{\"a\": 1, \"b\":\"x\"}");
+        assert_eq!(json["items"][0]["board_id"], 1234567890);
+        assert_eq!(json["items"][0]["pulse_id"], "1234567890");
+    }
+
+    #[test]
+    fn preferred_slot_picks_the_earliest_overlap() {
+        let request = AppointmentRequest {
+            availabilities: vec![AvailableTime::Evening, AvailableTime::Afternoon],
+            ..Default::default()
+        };
+
+        assert_eq!(request.preferred_slot(&[AvailableTime::Morning, AvailableTime::Afternoon, AvailableTime::Evening]), Some(AvailableTime::Afternoon));
+        assert_eq!(request.preferred_slot(&[AvailableTime::Morning]), None);
+    }
+
+    #[test]
+    fn change_set_with_status_and_phone() {
+        let changes = column_change_set(&[
+            ("status".to_string(), ColumnValue::status_change(&MessageStatus::Delivered)),
+            ("phone".to_string(), ColumnValue::phone_change(&PhoneNumber::parse("5551234567").unwrap())),
+        ]);
+
+        assert_eq!(changes, json!({
+            "status": { "label": "delivered" },
+            "phone": { "phone": "+15551234567", "countryShortName": "US" }
+        }));
+    }
+
+    #[test]
+    fn phone_change_country_follows_the_calling_code() {
+        let change = |e164| ColumnValue::phone_change(&PhoneNumber::from_e164(e164).unwrap());
+
+        assert_eq!(change("+442079460958"), json!({ "phone": "+442079460958", "countryShortName": "GB" }));
+        assert_eq!(change("+353861234567")["countryShortName"], "IE");
+        assert_eq!(change("+6561234567"), json!({ "phone": "+6561234567" }));
+
+        assert_eq!(PhoneNumber::parse("(415) 555-1234").unwrap().as_str(), "+14155551234");
+        assert!(PhoneNumber::parse("12").is_err());
+    }
+
+    #[test]
+    fn formatting_variants_hash_together() {
+        let leads = HashSet::from([lead("a", "(555) 123-4567"), lead("a", "1.555.123.4567")]);
+        assert_eq!(leads.len(), 1);
+    }
+
+    #[test]
+    fn null_phone_columns_are_skipped() {
+        let page = page(json!({ "items": [{ "name": "x", "id": "1", "column_values": [
+            { "id": "room", "text": "Room 1" },
+            { "id": "phone", "text": null, "value": null }
+        ] }] }));
+
+        let error = LeadDetails::try_from(page).unwrap_err();
+        assert!(matches!(error, SharedAdapterError::DataFieldNotFound { field, .. } if field == "phone_number"));
+    }
+
+    #[test]
+    fn phones_without_a_one_are_found() {
+        let page = page(json!({ "items": [{ "name": "x", "id": "1", "column_values": [
+            { "id": "date", "text": "2024-06-01" },
+            { "id": "phone", "text": "+44 20 7946 0958" }
+        ] }] }));
+
+        assert_eq!(LeadDetails::try_from(page).unwrap().phone_number, "442079460958");
+    }
+
+    #[test]
+    fn phone_columns_win_over_other_numbers() {
+        let typed = page(json!({ "items": [{ "name": "x", "id": "1", "column_values": [
+            { "id": "budget", "type": "numbers", "text": "1000000000" },
+            { "id": "phone", "type": "phone", "text": "+44 20 7946 0958" }
+        ] }] }));
+        assert_eq!(LeadDetails::try_from(typed).unwrap().phone_number, "442079460958");
+
+        let prefixed = page(json!({ "items": [{ "name": "x", "id": "1", "column_values": [
+            { "id": "budget", "text": "1000000000" },
+            { "id": "contact", "text": "+44 20 7946 0958" }
+        ] }] }));
+        assert_eq!(LeadDetails::try_from(prefixed).unwrap().phone_number, "442079460958");
+    }
+
+    #[test]
+    fn compact_line_parsing() {
+        let request: AppointmentRequest = "Jane|555-123-4567|morning,evening|call me|2024-06-01".parse().unwrap();
+        assert_eq!(request.name.as_deref(), Some("Jane"));
+        assert_eq!(request.phone_number, "15551234567");
+        assert_eq!(request.availabilities, vec![AvailableTime::Morning, AvailableTime::Evening]);
+        assert_eq!(request.requested_date, "2024-06-01");
+
+        assert!(matches!("a|b".parse::<AppointmentRequest>(), Err(SharedAdapterError::InvalidEncoding(_))));
+    }
+
+    #[test]
+    fn column_map_reports_missing_phone_column() {
+        let item = ItemBuilder::new().column("phone", "1").build();
+        let map = LeadColumnMap { phone: "phne".to_string(), ..Default::default() };
+
+        assert_eq!(map.validate_against(&item), Err(vec!["phne".to_string()]));
+    }
+
+    #[test]
+    fn cloned_appointment_keeps_its_fields() {
+        let request: AppointmentRequest = "Jane|555-123-4567|morning|n|2024-06-01".parse().unwrap();
+        let clone = request.clone();
+
+        assert_eq!(clone.name, request.name);
+        assert_eq!(clone.phone_number, request.phone_number);
+        assert_eq!(clone.availabilities, request.availabilities);
+        assert_eq!(clone.additional_information, request.additional_information);
+        assert_eq!(clone.requested_date, request.requested_date);
+    }
+
+    #[test]
+    fn waid_validation() {
+        assert!(update("15551234567", MessageStatus::Sent).is_valid_waid());
+        assert_eq!(update("15551234567", MessageStatus::Sent).to_phone_number().unwrap().as_str(), "+15551234567");
+        assert!(!update("+15551234567", MessageStatus::Sent).is_valid_waid());
+        assert!(!update("123456", MessageStatus::Sent).is_valid_waid());
+        // Niue numbers are the shortest in use: +683 and four digits
+        assert!(update("6831234", MessageStatus::Sent).is_valid_waid());
+        assert_eq!(update("6831234", MessageStatus::Sent).to_phone_number().unwrap().as_str(), "+6831234");
+    }
+
+    #[test]
+    fn canonical_json_ignores_column_order() {
+        let a = page(json!({ "items": [{ "id": "1", "column_values": [{ "id": "b", "text": "x" }, { "id": "a", "text": "y" }] }] }));
+        let b = page(json!({ "items": [{ "column_values": [{ "text": "y", "id": "a" }, { "text": "x", "id": "b" }], "id": "1" }] }));
+
+        assert_eq!(a.to_canonical_json(), b.to_canonical_json());
+    }
+
+    #[test]
+    fn sla_breaches_only_slow_deliveries() {
+        let events = vec![
+            ("ok".to_string(), MessageStatus::Sent, 0),
+            ("slow".to_string(), MessageStatus::Sent, 0),
+            ("ok".to_string(), MessageStatus::Delivered, 100),
+            ("slow".to_string(), MessageStatus::Delivered, 1000),
+        ];
+
+        assert_eq!(sla_breaches(&events, 300), vec!["slow".to_string()]);
+    }
+
+    #[test]
+    fn subitems_deserialize() {
+        let item: Item = serde_json::from_value(json!({ "id": "1", "subitems": [
+            { "id": "2", "name": "sub", "column_values": [{ "id": "date4", "text": "2024-06-01" }] }
+        ] })).unwrap();
+
+        assert_eq!(item.subitems().len(), 1);
+        assert_eq!(item.subitems()[0].column_text("date4"), Some("2024-06-01"));
+    }
+
+    #[test]
+    fn detailed_normalization_keeps_the_original() {
+        let phone = normalize_phone_detailed(" (555) 123-4567 ").unwrap();
+
+        assert_eq!(phone.original, " (555) 123-4567 ");
+        assert_eq!(phone.e164, "+15551234567");
+    }
+
+    #[test]
+    fn completeness_scores() {
+        let full = AppointmentRequest {
+            name: Some("Jane".to_string()),
+            phone_number: "5551234567".to_string(),
+            availabilities: vec![AvailableTime::Morning],
+            additional_information: "hi".to_string(),
+            requested_date: "2024-02-29".to_string()
+        };
+        assert_eq!(full.completeness(), 100);
+
+        let minimal = AppointmentRequest {
+            phone_number: "5551234567".to_string(),
+            requested_date: "2024-02-30".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(minimal.completeness(), 30);
+    }
+
+    #[test]
+    fn lead_from_form_fields() {
+        let form = |phone_key: &str| HashMap::from([
+            ("name".to_string(), "Jane".to_string()),
+            (phone_key.to_string(), "5551234567".to_string()),
+        ]);
+
+        assert_eq!(LeadDetails::try_from(form("phone")).unwrap().phone(), "15551234567");
+        assert_eq!(LeadDetails::try_from(form("phone_number")).unwrap().phone(), "15551234567");
+        assert!(matches!(LeadDetails::try_from(form("tel")), Err(SharedAdapterError::DataFieldNotFound { field, .. }) if field == "phone"));
+    }
+
+    #[test]
+    fn unmapped_provider_statuses_are_reported() {
+        let mapping = HashMap::from([
+            ("queued".to_string(), MessageStatus::Pending),
+            ("done".to_string(), MessageStatus::Delivered),
+        ]);
+
+        assert_eq!(validate_status_mapping(&mapping, &["queued", "bounced", "done"]), vec!["bounced".to_string()]);
+    }
+
+    #[test]
+    fn double_zero_prefix_is_international() {
+        assert_eq!(normalize_phone_detailed("0044 20 7946 0958").unwrap().e164, "+442079460958");
+        assert_eq!(normalize_phone_detailed("555-123-4567").unwrap().e164, "+15551234567");
+    }
+
+    #[test]
+    fn non_empty_columns_skip_empty_ones() {
+        let item: Item = serde_json::from_value(json!({ "id": "1", "column_values": [
+            { "id": "a", "text": "", "value": "{}" },
+            { "id": "b", "text": null, "value": null },
+            { "id": "c", "text": "hi", "value": "\"hi\"" },
+            { "id": "d", "text": "", "value": "{\"x\":1}" }
+        ] })).unwrap();
+
+        let ids: Vec<String> = item.non_empty_columns().into_iter().map(|column| column.id).collect();
+        assert_eq!(ids, ["c", "d"]);
+    }
+
+    #[test]
+    fn concrete_availabilities_drop_unknown() {
+        let request = AppointmentRequest {
+            availabilities: vec![AvailableTime::Morning, AvailableTime::Unknown, AvailableTime::Evening],
+            ..Default::default()
+        };
+
+        assert_eq!(request.concrete_availabilities().collect::<Vec<_>>(), vec![AvailableTime::Morning, AvailableTime::Evening]);
+    }
+
+    #[test]
+    fn lead_inputs_report_failures_by_index() {
+        let results = validate_lead_inputs(&[
+            ("a".to_string(), "5551234567".to_string()),
+            ("b".to_string(), "12".to_string()),
+        ]);
+
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err((1, SharedAdapterError::InvalidPhoneNumber(_)))));
+    }
+
+    #[test]
+    fn numeric_mirror_phone_values() {
+        let map = LeadColumnMap { phone: "phone".to_string(), ..Default::default() };
+        for value in [json!(15551234567u64), json!("{\"phone\":15551234567}")] {
+            let item: Item = serde_json::from_value(json!({ "name": "a", "id": "1", "column_values": [
+                { "id": "phone", "text": "", "value": value }
+            ] })).unwrap();
+
+            assert_eq!(LeadDetails::from_item(&item, &map).unwrap().phone(), "15551234567");
+        }
+    }
+
+    #[test]
+    fn csv_has_one_row_per_recipient() {
+        let csv = status_updates_to_csv(&[
+            update("a", MessageStatus::Sent),
+            update("b,\"c\"", MessageStatus::Sent),
+            update("a", MessageStatus::Read),
+            update("a", MessageStatus::Delivered),
+        ]);
+
+        assert_eq!(csv, "recipient_id,status\na,read\n\"b,\"\"c\"\"\",sent\n");
+    }
+
+    #[test]
+    fn streams_a_hundred_items() {
+        let items: Vec<serde_json::Value> = (0..100).map(|id| json!({ "name": format!("n{}", id), "id": id.to_string() })).collect();
+        let json = serde_json::to_string_pretty(&items).unwrap();
+
+        let results: Vec<_> = stream_items(json.as_bytes()).collect();
+        assert_eq!(results.len(), 100);
+        assert!(results.iter().all(Result::is_ok));
+
+        let mut malformed = stream_items("[{\"name\":1}]".as_bytes());
+        assert!(malformed.next().unwrap().is_err());
+        assert!(malformed.next().is_none());
+    }
+
+    #[test]
+    fn stale_recipients() {
+        let ages = vec![
+            ("stale".to_string(), StatusAge { status: MessageStatus::Sent, changed_at: 0 }),
+            ("fresh".to_string(), StatusAge { status: MessageStatus::Sent, changed_at: 900 }),
+        ];
+
+        assert_eq!(stale_in_status(&ages, MessageStatus::Sent, 600, 1000), vec!["stale".to_string()]);
+    }
+
+    #[test]
+    fn name_falls_back_to_name_column() {
+        let item = ItemBuilder::new().name("").column("phone", "5551234567").column("contact", "Jane").build();
+        let mut map = LeadColumnMap {
+            phone: "phone".to_string(),
+            name_col: Some("contact".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(LeadDetails::from_item(&item, &map).unwrap().name, "Jane");
+
+        map.name_col = None;
+        map.name_placeholder = Some("Unknown lead".to_string());
+        assert_eq!(LeadDetails::from_item(&item, &map).unwrap().name, "Unknown lead");
+    }
+
+    #[test]
+    fn national_format() {
+        assert_eq!(PhoneNumber::from_e164("+15551234567").unwrap().to_national(), "(555) 123-4567");
+        assert_eq!(PhoneNumber::from_e164("+442079460958").unwrap().to_national(), "+442079460958");
+        assert_eq!(PhoneNumber("+1ééééé".to_string()).to_national(), "+1ééééé");
+    }
+
+    #[test]
+    fn items_without_column_values() {
+        let item: Item = serde_json::from_str(r#"{"name":"a","id":"1"}"#).unwrap();
+
+        assert!(item.non_empty_columns().is_empty());
+        assert!(item.column_text("phone").is_none());
+    }
+
+    #[test]
+    fn none_fields_are_omitted() {
+        let json = serde_json::to_value(update("1", MessageStatus::Sent)).unwrap();
+        assert_eq!(json, json!({ "recipient_id": "1", "status": "sent" }));
+    }
+
+    #[test]
+    fn tracker_logs_stale_updates() {
+        let mut tracker = StatusTracker::new();
+        tracker.update("a", MessageStatus::Sent);
+        tracker.update("a", MessageStatus::Read);
+        tracker.update("a", MessageStatus::Delivered);
+
+        assert_eq!(tracker.status("a"), Some(&MessageStatus::Read));
+        assert_eq!(tracker.downgrades(), &[Downgrade {
+            recipient: "a".to_string(),
+            from: MessageStatus::Read,
+            to: MessageStatus::Delivered
+        }]);
+    }
+
+    #[test]
+    fn trunk_zero_is_dropped() {
+        assert_eq!(normalize_phone_detailed("+44 (0)20 7946 0958").unwrap().e164, "+442079460958");
+        assert_eq!(normalize_phone_detailed("+49 0151 23456789").unwrap().e164, "+4915123456789");
+        assert_eq!(normalize_phone_detailed("+39 06 1234 5678").unwrap().e164, "+390612345678");
+    }
+
+    #[test]
+    fn plus_numbers_keep_their_country_code() {
+        assert_eq!(normalize_phone_detailed("+65 6123 4567").unwrap().e164, "+6561234567");
+        assert_eq!(normalize_phone_detailed("+62 812 3456 7890").unwrap().e164, "+6281234567890");
+        assert_eq!(normalize_phone_detailed("+1 (555) 123-4567").unwrap().e164, "+15551234567");
+        assert!(normalize_phone_detailed("+123456").is_err());
+    }
+}
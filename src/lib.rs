@@ -1,16 +1,41 @@
-use std::{cmp::Ordering, collections::HashMap};
-use serde::{Deserialize, Serialize};
-use thiserror::Error;
+//! Without the default `std` feature only the `AvailableTime` and `MessageStatus` logic is
+//! built, as `no_std` + `alloc`. Everything touching JSON or Monday/WhatsApp payloads needs `std`.
+#![cfg_attr(not(feature = "std"), no_std)]
 
-#[derive(Debug, Error)]
-pub enum SharedAdapterError {
-    #[error("Invalid phone number: {0}")]
-    InvalidPhoneNumber(String),
-    #[error("Data field not found: {0}")]
-    DataFieldNotFound(String)
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::{String, ToString}, vec::Vec};
+use core::{cmp::{Ordering, Reverse}, fmt};
+use serde::{de::{self, Visitor}, Deserialize, Deserializer, Serialize};
+
+#[cfg(feature = "std")]
+mod adapter;
+#[cfg(feature = "std")]
+pub use adapter::*;
+
+/// The types most users need. Helper types such as `LeadIter` are left out on purpose.
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use shared_monday::prelude::*;
+///
+/// let lead = LeadDetails::new("Jane".to_string(), "5551234567".to_string()).unwrap();
+/// assert_eq!(lead.phone(), "15551234567");
+/// assert!(MessageStatus::Sent < MessageStatus::Read);
+/// # }
+/// ```
+pub mod prelude {
+    pub use crate::{AvailableTime, MessageStatus};
+
+    #[cfg(feature = "std")]
+    pub use crate::{
+        AppointmentRequest, ColumnValue, Item, ItemsPage, LeadDetails, MessageProvider, SharedAdapterError,
+        StatusReconciler, StatusUpdate
+    };
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum AvailableTime {
     Morning,
@@ -19,6 +44,104 @@ pub enum AvailableTime {
     Unknown
 }
 
+impl AvailableTime {
+    /// Buckets a 24-hour clock hour: before noon is morning, noon until 5pm is afternoon,
+    /// and 5pm onwards is evening
+    pub fn from_hour(hour: u8) -> AvailableTime {
+        match hour {
+            0..=11 => AvailableTime::Morning,
+            12..=16 => AvailableTime::Afternoon,
+            17..=23 => AvailableTime::Evening,
+            _ => AvailableTime::Unknown
+        }
+    }
+
+    /// The hour (24h clock) to send a reminder for this slot: one hour before the slot's
+    /// usual start of 9am, 12pm or 5pm
+    pub fn reminder_hour(&self) -> Option<u8> {
+        match self {
+            AvailableTime::Morning => Some(8),
+            AvailableTime::Afternoon => Some(11),
+            AvailableTime::Evening => Some(16),
+            AvailableTime::Unknown => None
+        }
+    }
+
+    /// Best-effort parsing of free-form text such as `"9am"`, `"after 5"`, `"noon"` or `"evenings"`.
+    ///
+    /// Day-part words win over clock times. Otherwise the first number is read as an hour,
+    /// honouring an `am`/`pm` suffix. Without a suffix, 1 through 7 are assumed to be pm
+    /// since nobody books a call at 3 in the morning.
+    ///
+    /// A reply that is nothing but shorthand or an emoji is handed to `from_shorthand` first.
+    pub fn from_free_text(text: &str) -> AvailableTime {
+        let text = text.to_lowercase();
+
+        let shorthand = AvailableTime::from_shorthand(&text);
+        if shorthand != AvailableTime::Unknown {
+            return shorthand;
+        }
+
+        if text.contains("morning") {
+            return AvailableTime::Morning;
+        }
+        if text.contains("afternoon") || text.contains("noon") || text.contains("midday") {
+            return AvailableTime::Afternoon;
+        }
+        if text.contains("evening") || text.contains("night") {
+            return AvailableTime::Evening;
+        }
+
+        let Some(start) = text.find(|c: char| c.is_ascii_digit()) else {
+            return AvailableTime::Unknown;
+        };
+        let digits: String = text[start..].chars().take_while(|c| c.is_ascii_digit()).collect();
+        let Ok(mut hour) = digits.parse::<u8>() else {
+            return AvailableTime::Unknown;
+        };
+
+        // Skip past any minutes so "5:30pm" still finds its suffix
+        let rest = text[start + digits.len()..]
+            .trim_start_matches(|c: char| c == ':' || c.is_ascii_digit())
+            .trim_start()
+            .replace('.', "");
+
+        if rest.starts_with("pm") {
+            if hour < 12 {
+                hour += 12;
+            }
+        } else if rest.starts_with("am") {
+            if hour == 12 {
+                hour = 0;
+            }
+        } else if (1..=7).contains(&hour) {
+            hour += 12;
+        }
+
+        AvailableTime::from_hour(hour)
+    }
+
+    /// Recognizes a whole reply made of shorthand or a single emoji, ignoring case and
+    /// surrounding whitespace:
+    ///
+    /// | Slot      | Shorthand                                      | Emoji |
+    /// |-----------|------------------------------------------------|-------|
+    /// | Morning   | `am`, `morn`, `morning`, `mornings`            | 🌅    |
+    /// | Afternoon | `pm`, `aft`, `afternoon`, `afternoons`, `arvo` | ☀️    |
+    /// | Evening   | `eve`, `evening`, `evenings`, `tonight`        | 🌙    |
+    ///
+    /// Anything else is `Unknown`.
+    pub fn from_shorthand(text: &str) -> AvailableTime {
+        // The sun is sent both with and without the emoji variation selector
+        match text.trim().to_lowercase().trim_end_matches('\u{fe0f}') {
+            "am" | "morn" | "morning" | "mornings" | "\u{1f305}" => AvailableTime::Morning,
+            "pm" | "aft" | "afternoon" | "afternoons" | "arvo" | "\u{2600}" => AvailableTime::Afternoon,
+            "eve" | "evening" | "evenings" | "tonight" | "\u{1f319}" => AvailableTime::Evening,
+            _ => AvailableTime::Unknown
+        }
+    }
+}
+
 impl From<String> for AvailableTime {
     fn from(time: String) -> Self {
         match time.to_lowercase().as_str() {
@@ -41,10 +164,50 @@ impl From<AvailableTime> for String {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum DayType {
+    Weekday,
+    Weekend,
+    Any
+}
+
+/// A part of the day on a kind of day, e.g. "weekday mornings"
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct TimePreference {
+    pub time: AvailableTime,
+    pub day: DayType,
+}
+
+impl From<String> for TimePreference {
+    /// Parses phrases like `"weekend evening"`. The time of day is read with
+    /// `AvailableTime::from_free_text`, and a missing day type means `Any`.
+    fn from(preference: String) -> Self {
+        let lowercase = preference.to_lowercase();
+
+        let day = if ["weekend", "saturday", "sunday"].iter().any(|word| lowercase.contains(word)) {
+            DayType::Weekend
+        } else if ["weekday", "monday", "tuesday", "wednesday", "thursday", "friday"].iter().any(|word| lowercase.contains(word)) {
+            DayType::Weekday
+        } else {
+            DayType::Any
+        };
+
+        TimePreference {
+            time: AvailableTime::from_free_text(&lowercase),
+            day
+        }
+    }
+}
+
+/// Serializes to its wire name. Deserializing is lenient, see the `Deserialize` impl.
+#[derive(Serialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
 pub enum MessageStatus {
+    #[default]
     #[serde(rename = "not sent")]
     Unknown,
+    #[serde(rename = "scheduled")]
+    Scheduled,
     #[serde(rename = "pending")]
     Pending,
     #[serde(rename = "unavailable")]
@@ -63,57 +226,248 @@ pub enum MessageStatus {
     Unsubscribed,
 }
 
-impl Default for MessageStatus {
-    fn default() -> Self {
-        MessageStatus::Unknown
+impl MessageStatus {
+
+    /// Every status, in index order
+    pub const fn all() -> [MessageStatus; 10] {
+        [
+            MessageStatus::Unknown,
+            MessageStatus::Scheduled,
+            MessageStatus::Pending,
+            MessageStatus::Unavailable,
+            MessageStatus::Failed,
+            MessageStatus::Sent,
+            MessageStatus::Delivered,
+            MessageStatus::Read,
+            MessageStatus::Responded,
+            MessageStatus::Unsubscribed,
+        ]
     }
-}
 
-impl MessageStatus {
+    /// The string this status serializes to
+    pub fn wire_name(&self) -> &'static str {
+        match self {
+            MessageStatus::Unknown => "not sent",
+            MessageStatus::Scheduled => "scheduled",
+            MessageStatus::Pending => "pending",
+            MessageStatus::Unavailable => "unavailable",
+            MessageStatus::Failed => "failed",
+            MessageStatus::Sent => "sent",
+            MessageStatus::Delivered => "delivered",
+            MessageStatus::Read => "read",
+            MessageStatus::Responded => "responded",
+            MessageStatus::Unsubscribed => "unsubscribed",
+        }
+    }
 
-    pub fn to_index(&self) -> u8 {
+    pub const fn to_index(&self) -> u8 {
         match self {
             MessageStatus::Unknown => 0,
-            MessageStatus::Pending => 1,
-            MessageStatus::Unavailable => 2,
-            MessageStatus::Failed => 3,
-            MessageStatus::Sent => 4,
-            MessageStatus::Delivered => 5,
-            MessageStatus::Read => 6,
-            MessageStatus::Responded => 7,
-            MessageStatus::Unsubscribed => 8,
+            MessageStatus::Scheduled => 1,
+            MessageStatus::Pending => 2,
+            MessageStatus::Unavailable => 3,
+            MessageStatus::Failed => 4,
+            MessageStatus::Sent => 5,
+            MessageStatus::Delivered => 6,
+            MessageStatus::Read => 7,
+            MessageStatus::Responded => 8,
+            MessageStatus::Unsubscribed => 9,
         }
     }
 
-    pub fn to_string(&self) -> String {
-        match self {
-            MessageStatus::Sent => "sent".to_string(),
-            MessageStatus::Delivered => "delivered".to_string(),
-            MessageStatus::Read => "read".to_string(),
-            MessageStatus::Failed => "failed".to_string(),
-            MessageStatus::Pending => "pending".to_string(),
-            MessageStatus::Responded => "responded".to_string(),
-            MessageStatus::Unsubscribed => "unsubscribed".to_string(),
-            MessageStatus::Unavailable => "unavailable".to_string(),
-            _ => "unknown".to_string(),
+    pub fn from_index(index: u8) -> Option<MessageStatus> {
+        MessageStatus::all().into_iter().find(|status| status.to_index() == index)
+    }
+
+    /// Maps the numeric codes of our legacy provider, which are unrelated to `to_index`
+    pub fn from_legacy_code(code: u16) -> MessageStatus {
+        match code {
+            200 => MessageStatus::Delivered,
+            202 => MessageStatus::Sent,
+            404 => MessageStatus::Unavailable,
+            500 => MessageStatus::Failed,
+            _ => MessageStatus::Unknown,
         }
     }
 
+    /// Looks the status up by wire name, so it can't drift from `wire_name`. Anything else,
+    /// including `Unknown`'s display form `"unknown"`, is `Unknown`.
     pub fn from_string(status: &str) -> MessageStatus {
-        match status {
-            "sent" => MessageStatus::Sent,
-            "delivered" => MessageStatus::Delivered,
-            "read" => MessageStatus::Read,
-            "failed" => MessageStatus::Failed,
-            "pending" => MessageStatus::Pending,
-            "responded" => MessageStatus::Responded,
-            "unsubscribed" => MessageStatus::Unsubscribed,
-            "unavailable" => MessageStatus::Unavailable,
-            _ => MessageStatus::Unknown,
+        MessageStatus::all()
+            .into_iter()
+            .find(|candidate| candidate.wire_name() == status)
+            .unwrap_or_default()
+    }
+
+    /// Statuses that end the lifecycle of a message; nothing moves past them
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, MessageStatus::Failed | MessageStatus::Unavailable | MessageStatus::Unsubscribed)
+    }
+
+    /// Whether a message currently in this status may move to `next`.
+    /// Statuses only move forward, except that a terminal status may be reached from any
+    /// non-terminal one (a `failed` commonly arrives after `sent`).
+    pub fn can_transition_to(&self, next: &MessageStatus) -> bool {
+        if self.is_terminal() {
+            return false;
+        }
+
+        next.is_terminal() || next > self
+    }
+
+    /// Whether the message reached the user. `Scheduled`, `Pending`, `Sent` and `Unknown` are neither
+    /// a success nor a failure.
+    pub fn is_success(&self) -> bool {
+        matches!(self, MessageStatus::Delivered | MessageStatus::Read | MessageStatus::Responded)
+    }
+
+    /// Whether the message will never reach the user, which is exactly the terminal statuses
+    pub fn is_failure(&self) -> bool {
+        self.is_terminal()
+    }
+
+    /// The status that should follow this one on the happy path
+    /// (`Scheduled` → `Pending` → `Sent` → `Delivered` → `Read` → `Responded`)
+    pub fn next_expected(&self) -> Option<MessageStatus> {
+        match self {
+            MessageStatus::Unknown | MessageStatus::Scheduled => Some(MessageStatus::Pending),
+            MessageStatus::Pending => Some(MessageStatus::Sent),
+            MessageStatus::Sent => Some(MessageStatus::Delivered),
+            MessageStatus::Delivered => Some(MessageStatus::Read),
+            MessageStatus::Read => Some(MessageStatus::Responded),
+            MessageStatus::Responded
+            | MessageStatus::Failed
+            | MessageStatus::Unavailable
+            | MessageStatus::Unsubscribed => None,
+        }
+    }
+
+    /// Who to message first when sends are limited; higher goes first. Recipients who read
+    /// but didn't respond are the best re-engagement targets, followed by those further
+    /// from reading. Recipients who already responded come late, and ones that can't be
+    /// messaged at all come last.
+    pub fn priority_weight(&self) -> u8 {
+        match self {
+            MessageStatus::Read => 7,
+            MessageStatus::Delivered => 6,
+            MessageStatus::Sent => 5,
+            MessageStatus::Unknown => 4,
+            MessageStatus::Pending => 3,
+            MessageStatus::Scheduled => 2,
+            MessageStatus::Responded => 1,
+            MessageStatus::Failed | MessageStatus::Unavailable | MessageStatus::Unsubscribed => 0,
+        }
+    }
+
+    /// How a status should be presented, e.g. the color of a UI badge
+    pub fn severity(&self) -> Severity {
+        match self {
+            MessageStatus::Delivered | MessageStatus::Read | MessageStatus::Responded => Severity::Success,
+            MessageStatus::Failed | MessageStatus::Unavailable => Severity::Error,
+            MessageStatus::Unsubscribed => Severity::Warning,
+            MessageStatus::Unknown
+            | MessageStatus::Scheduled
+            | MessageStatus::Pending
+            | MessageStatus::Sent => Severity::Info,
+        }
+    }
+
+    /// WhatsApp-style ticks for a status. `Read` shares `Delivered`'s double tick, which
+    /// WhatsApp tells apart only by coloring it blue, so callers wanting that must style it.
+    pub fn as_emoji(&self) -> &'static str {
+        match self {
+            MessageStatus::Unknown => "❔",
+            MessageStatus::Scheduled => "📅",
+            MessageStatus::Pending => "🕓",
+            MessageStatus::Sent => "✓",
+            MessageStatus::Delivered | MessageStatus::Read => "✓✓",
+            MessageStatus::Responded => "💬",
+            MessageStatus::Failed | MessageStatus::Unavailable => "⚠️",
+            MessageStatus::Unsubscribed => "🚫",
         }
     }
 }
 
+/// Sorts recipients by `MessageStatus::priority_weight`, highest first, keeping the
+/// existing order among equal weights
+pub fn sort_by_priority(recipients: &mut [(String, MessageStatus)]) {
+    recipients.sort_by_key(|(_, status)| Reverse(status.priority_weight()));
+}
+
+/// Every step of an observed status sequence that `can_transition_to` rejects, as
+/// `(index, from, to)` where `index` is the position of `to` in the sequence
+pub fn validate_sequence(seq: &[MessageStatus]) -> Vec<(usize, MessageStatus, MessageStatus)> {
+    seq.windows(2)
+        .enumerate()
+        .filter(|(_, pair)| !pair[0].can_transition_to(&pair[1]))
+        .map(|(i, pair)| (i + 1, pair[0].clone(), pair[1].clone()))
+        .collect()
+}
+
+// `all()` must list every status in index order, otherwise `from_index` can't find it.
+// `wire_name`, `to_index` and `Display` match exhaustively, and the conversions that look
+// statuses up go through `all()`; the tests check that `all()` is missing nothing.
+const _: () = {
+    let all = MessageStatus::all();
+    let mut index = 0;
+    while index < all.len() {
+        assert!(all[index].to_index() as usize == index, "MessageStatus::all() is out of sync with to_index");
+        index += 1;
+    }
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error
+}
+
+impl<'de> Deserialize<'de> for MessageStatus {
+    /// Matches wire names case-insensitively, so `"Delivered"` and `"DELIVERED"` both work.
+    /// The legacy `"unknown"` and anything unrecognized become `Unknown`.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StatusVisitor;
+
+        impl Visitor<'_> for StatusVisitor {
+            type Value = MessageStatus;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a message status string")
+            }
+
+            fn visit_str<E: de::Error>(self, status: &str) -> Result<MessageStatus, E> {
+                Ok(MessageStatus::all()
+                    .into_iter()
+                    .find(|candidate| candidate.wire_name().eq_ignore_ascii_case(status))
+                    .unwrap_or(MessageStatus::Unknown))
+            }
+        }
+
+        deserializer.deserialize_str(StatusVisitor)
+    }
+}
+
+impl fmt::Display for MessageStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            MessageStatus::Sent => "sent",
+            MessageStatus::Delivered => "delivered",
+            MessageStatus::Read => "read",
+            MessageStatus::Failed => "failed",
+            MessageStatus::Scheduled => "scheduled",
+            MessageStatus::Pending => "pending",
+            MessageStatus::Responded => "responded",
+            MessageStatus::Unsubscribed => "unsubscribed",
+            MessageStatus::Unavailable => "unavailable",
+            MessageStatus::Unknown => "unknown",
+        };
+        f.write_str(status)
+    }
+}
+
 impl PartialOrd for MessageStatus {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -125,85 +479,266 @@ impl Ord for MessageStatus {
         self.to_index().cmp(&other.to_index())
     }
 }
-/// Represents a status update regarding a WhatsApp message
-#[derive(Debug, Serialize, Deserialize)]
-pub struct StatusUpdate {
-    pub recipient_id: String,
-    pub status: MessageStatus,
-}
 
-/// Represents a completed appointment request
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AppointmentRequest {
-    pub name: Option<String>,
-    pub phone_number: String,
-    pub availabilities: Vec<AvailableTime>,
-    pub additional_information: String,
-    pub requested_date: String
+/// Ordered history of one recipient's status changes. Only legal forward transitions
+/// are recorded, so repeated or stale statuses are dropped.
+#[derive(Debug, Default, Clone)]
+pub struct StatusTimeline {
+    history: Vec<MessageStatus>,
 }
 
+impl StatusTimeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Item {
-    name: Option<String>,
-    id: Option<String>,
-    column_values: Option<Vec<HashMap<String, serde_json::Value>>>,
-}
+    pub fn push(&mut self, status: MessageStatus) {
+        let accepted = match self.history.last() {
+            Some(latest) => latest.can_transition_to(&status),
+            None => true
+        };
+
+        if accepted {
+            self.history.push(status);
+        }
+    }
 
+    pub fn latest(&self) -> Option<&MessageStatus> {
+        self.history.last()
+    }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ItemsPage {
-    items: Vec<Item>,
+    pub fn history(&self) -> &[MessageStatus] {
+        &self.history
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
 
-#[derive(Deserialize, Serialize, Debug)]
-pub struct LeadDetails {
-    pub name: String,
-    pub phone_number: String
-}
+    use super::*;
+
+    #[test]
+    fn free_text_reads_clock_times_and_day_parts() {
+        assert_eq!(AvailableTime::from_free_text("9am"), AvailableTime::Morning);
+        assert_eq!(AvailableTime::from_free_text("6pm"), AvailableTime::Evening);
+        assert_eq!(AvailableTime::from_free_text("noon"), AvailableTime::Afternoon);
+        assert_eq!(AvailableTime::from_free_text("after 5"), AvailableTime::Evening);
+        assert_eq!(AvailableTime::from_free_text("2:30 p.m."), AvailableTime::Afternoon);
+        assert_eq!(AvailableTime::from_free_text("Evenings"), AvailableTime::Evening);
+        assert_eq!(AvailableTime::from_free_text("whenever"), AvailableTime::Unknown);
+    }
+
+    #[test]
+    fn reminder_hour_is_an_hour_before_each_slot() {
+        assert_eq!(AvailableTime::Morning.reminder_hour(), Some(8));
+        assert_eq!(AvailableTime::Afternoon.reminder_hour(), Some(11));
+        assert_eq!(AvailableTime::Evening.reminder_hour(), Some(16));
+        assert_eq!(AvailableTime::Unknown.reminder_hour(), None);
+    }
+
+    #[test]
+    fn time_preference_reads_day_and_time() {
+        let preference = TimePreference::from("Weekend evening".to_string());
+        assert_eq!(preference, TimePreference { time: AvailableTime::Evening, day: DayType::Weekend });
+
+        assert_eq!(TimePreference::from("weekday mornings".to_string()).day, DayType::Weekday);
+        assert_eq!(TimePreference::from("9am".to_string()).day, DayType::Any);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn time_preference_serializes_both_parts() {
+        let preference = TimePreference::from("Weekend evening".to_string());
+        assert_eq!(serde_json::to_value(&preference).unwrap(), serde_json::json!({ "time": "evening", "day": "weekend" }));
+    }
 
-impl LeadDetails {
-    pub fn new(name: String, mut phone_number: String) -> Result<LeadDetails, SharedAdapterError> {
+    #[test]
+    fn emoji_and_shorthand_map_to_slots() {
+        assert_eq!(AvailableTime::from_free_text("🌅"), AvailableTime::Morning);
+        assert_eq!(AvailableTime::from_free_text("☀️"), AvailableTime::Afternoon);
+        assert_eq!(AvailableTime::from_free_text("🌙"), AvailableTime::Evening);
+        assert_eq!(AvailableTime::from_free_text(" AM "), AvailableTime::Morning);
+        assert_eq!(AvailableTime::from_shorthand("aft"), AvailableTime::Afternoon);
+        assert_eq!(AvailableTime::from_shorthand("eve"), AvailableTime::Evening);
+        assert_eq!(AvailableTime::from_shorthand("whenever"), AvailableTime::Unknown);
 
-        // Ensure that the phone number is either 10 or 11 digits
-        if phone_number.len() != 10 && phone_number.len() != 11 {
-            return Err(SharedAdapterError::InvalidPhoneNumber(phone_number));
+        for (slot, words) in [
+            (AvailableTime::Morning, ["morning", "mornings"]),
+            (AvailableTime::Afternoon, ["afternoon", "afternoons"]),
+            (AvailableTime::Evening, ["evening", "evenings"]),
+        ] {
+            for word in words {
+                assert_eq!(AvailableTime::from_shorthand(word), slot);
+            }
         }
+    }
 
-        // If the phone number is 10 digits, add a '1' to the beginning
-        if phone_number.len() == 10 {
-            phone_number.insert(0, '1');
+    /// The status declared after this one. The match is exhaustive on purpose: a new
+    /// variant doesn't compile until it is linked in here, after which
+    /// `all_lists_every_status` fails until `all()` has it too.
+    fn declared_after(status: &MessageStatus) -> Option<MessageStatus> {
+        match status {
+            MessageStatus::Unknown => Some(MessageStatus::Scheduled),
+            MessageStatus::Scheduled => Some(MessageStatus::Pending),
+            MessageStatus::Pending => Some(MessageStatus::Unavailable),
+            MessageStatus::Unavailable => Some(MessageStatus::Failed),
+            MessageStatus::Failed => Some(MessageStatus::Sent),
+            MessageStatus::Sent => Some(MessageStatus::Delivered),
+            MessageStatus::Delivered => Some(MessageStatus::Read),
+            MessageStatus::Read => Some(MessageStatus::Responded),
+            MessageStatus::Responded => Some(MessageStatus::Unsubscribed),
+            MessageStatus::Unsubscribed => None,
+        }
+    }
+
+    #[test]
+    fn all_lists_every_status() {
+        let mut declared = vec![MessageStatus::Unknown];
+        while let Some(next) = declared.last().and_then(declared_after) {
+            declared.push(next);
         }
 
-        Ok(LeadDetails {
-            name,
-            phone_number
-        })
+        assert_eq!(declared, MessageStatus::all());
+    }
+
+    #[test]
+    fn string_conversions_round_trip() {
+        for status in MessageStatus::all() {
+            assert_eq!(MessageStatus::from_string(&status.to_string()), status);
+            assert_eq!(MessageStatus::from_string(status.wire_name()), status);
+        }
+    }
+
+    #[test]
+    fn index_conversions_round_trip() {
+        for status in MessageStatus::all() {
+            assert_eq!(MessageStatus::from_index(status.to_index()), Some(status.clone()));
+        }
+        assert_eq!(MessageStatus::from_index(MessageStatus::all().len() as u8), None);
     }
-}
 
-impl TryFrom<ItemsPage> for LeadDetails {
-    type Error = SharedAdapterError;
+    #[cfg(feature = "std")]
+    #[test]
+    fn serde_round_trips() {
+        for status in MessageStatus::all() {
+            let json = serde_json::to_string(&status).unwrap();
+            assert_eq!(json, format!("\"{}\"", status.wire_name()));
+            assert_eq!(serde_json::from_str::<MessageStatus>(&json).unwrap(), status);
+        }
+    }
+
+    #[test]
+    fn display_matches_wire_names_except_unknown() {
+        assert_eq!(MessageStatus::Sent.to_string(), "sent");
+        assert_eq!(MessageStatus::Unknown.to_string(), "unknown");
+    }
+
+    #[test]
+    fn severity_classifies_every_status() {
+        let expected = [
+            (MessageStatus::Unknown, Severity::Info),
+            (MessageStatus::Scheduled, Severity::Info),
+            (MessageStatus::Pending, Severity::Info),
+            (MessageStatus::Unavailable, Severity::Error),
+            (MessageStatus::Failed, Severity::Error),
+            (MessageStatus::Sent, Severity::Info),
+            (MessageStatus::Delivered, Severity::Success),
+            (MessageStatus::Read, Severity::Success),
+            (MessageStatus::Responded, Severity::Success),
+            (MessageStatus::Unsubscribed, Severity::Warning),
+        ];
 
-    fn try_from(items_page: ItemsPage) -> Result<Self, Self::Error> {
-        let item = items_page.items.first().ok_or(SharedAdapterError::DataFieldNotFound("items".to_string()))?;
+        assert_eq!(expected.len(), MessageStatus::all().len());
+        for (status, severity) in expected {
+            assert_eq!(status.severity(), severity, "{}", status);
+        }
+    }
 
-        let name = item.name.clone().ok_or(SharedAdapterError::DataFieldNotFound("name".to_string()))?;
-        let column_values = item.column_values.clone().ok_or(SharedAdapterError::DataFieldNotFound("column_values".to_string()))?;
+    #[test]
+    fn next_expected_follows_the_chain() {
+        assert_eq!(MessageStatus::Pending.next_expected(), Some(MessageStatus::Sent));
+        assert_eq!(MessageStatus::Sent.next_expected(), Some(MessageStatus::Delivered));
+        assert_eq!(MessageStatus::Delivered.next_expected(), Some(MessageStatus::Read));
+        assert_eq!(MessageStatus::Failed.next_expected(), None);
+    }
 
-        let phone_number = column_values
-            .iter()
-            .find(|column_value| {
-                column_value.get("text").map(|text| text.as_str().unwrap_or_default().contains("1")).unwrap_or_default()
-            })
-            .ok_or(SharedAdapterError::DataFieldNotFound("phone_number".to_string()))?
-            .get("text")
-            .ok_or(SharedAdapterError::DataFieldNotFound("text".to_string()))?
-            .as_str()
-            .ok_or(SharedAdapterError::DataFieldNotFound("text".to_string()))?
-            .to_string();
+    #[test]
+    fn success_and_failure_cover_every_status() {
+        for status in MessageStatus::all() {
+            let success = matches!(status, MessageStatus::Delivered | MessageStatus::Read | MessageStatus::Responded);
+            let failure = matches!(status, MessageStatus::Failed | MessageStatus::Unavailable | MessageStatus::Unsubscribed);
 
-        Ok(LeadDetails::new(name, phone_number)?)
+            assert_eq!(status.is_success(), success, "{}", status);
+            assert_eq!(status.is_failure(), failure, "{}", status);
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn scheduled_comes_before_pending() {
+        assert!(MessageStatus::Unknown < MessageStatus::Scheduled);
+        assert!(MessageStatus::Scheduled < MessageStatus::Pending);
+        assert!(MessageStatus::Scheduled.can_transition_to(&MessageStatus::Pending));
+        assert!(!MessageStatus::Pending.can_transition_to(&MessageStatus::Scheduled));
+    }
+
+    #[test]
+    fn legacy_codes_map_to_statuses() {
+        assert_eq!(MessageStatus::from_legacy_code(200), MessageStatus::Delivered);
+        assert_eq!(MessageStatus::from_legacy_code(202), MessageStatus::Sent);
+        assert_eq!(MessageStatus::from_legacy_code(404), MessageStatus::Unavailable);
+        assert_eq!(MessageStatus::from_legacy_code(500), MessageStatus::Failed);
+        assert_eq!(MessageStatus::from_legacy_code(418), MessageStatus::Unknown);
+    }
+
+    #[test]
+    fn priority_puts_read_above_pending() {
+        let mut recipients = vec![
+            ("pending".to_string(), MessageStatus::Pending),
+            ("read".to_string(), MessageStatus::Read),
+            ("failed".to_string(), MessageStatus::Failed),
+        ];
+        sort_by_priority(&mut recipients);
+
+        assert_eq!(recipients[0].0, "read");
+        assert_eq!(recipients[2].0, "failed");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn deserializes_case_insensitively() {
+        let parse = |json: &str| serde_json::from_str::<MessageStatus>(json).unwrap();
+
+        assert_eq!(parse("\"Delivered\""), MessageStatus::Delivered);
+        assert_eq!(parse("\"DELIVERED\""), MessageStatus::Delivered);
+        assert_eq!(parse("\"unknown\""), MessageStatus::Unknown);
+        assert_eq!(parse("\"bogus\""), MessageStatus::Unknown);
+        assert_eq!(serde_json::to_string(&MessageStatus::Unknown).unwrap(), "\"not sent\"");
+    }
+
+    #[test]
+    fn every_status_has_an_emoji() {
+        for status in MessageStatus::all() {
+            assert!(!status.as_emoji().is_empty(), "{}", status);
+        }
+        assert_eq!(MessageStatus::Delivered.as_emoji(), "✓✓");
+    }
+
+    #[test]
+    fn validate_sequence_reports_backward_jumps() {
+        let sequence = [MessageStatus::Pending, MessageStatus::Sent, MessageStatus::Read, MessageStatus::Delivered];
+        assert_eq!(validate_sequence(&sequence), vec![(3, MessageStatus::Read, MessageStatus::Delivered)]);
+    }
+
+    #[test]
+    fn timeline_drops_backward_statuses() {
+        let mut timeline = StatusTimeline::new();
+        for status in [MessageStatus::Pending, MessageStatus::Sent, MessageStatus::Sent, MessageStatus::Delivered, MessageStatus::Pending] {
+            timeline.push(status);
+        }
+
+        assert_eq!(timeline.history(), &[MessageStatus::Pending, MessageStatus::Sent, MessageStatus::Delivered]);
+        assert_eq!(timeline.latest(), Some(&MessageStatus::Delivered));
+    }
+}